@@ -0,0 +1,288 @@
+use crate::events::{Landed, PlatformReached, RunStarted};
+use crate::player::CenterPlayer;
+use crate::stats::Stats;
+use crate::ui::{text, TEXT_COLOR, TITLE_COLOR};
+use crate::world::platform::Platform;
+use crate::{get_state_directory, FontAssets, GameMode, Height, PauseState, Run, HALF_WORLD_SIZE};
+use bevy::app::App;
+use bevy::hierarchy::{BuildChildren, DespawnRecursiveExt};
+use bevy::prelude::{
+    default, in_state, not, resource_equals, Color, Commands, Component, Entity, Event,
+    EventReader, EventWriter, FixedUpdate, FlexDirection, IntoSystemConfigs, NodeBundle, Plugin,
+    PositionType, Query, Res, ResMut, Resource, Style, Time, Timer, TimerMode, Transform, UiRect,
+    Update, Val, With,
+};
+use bevy_persistent::{Persistent, StorageFormat};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::time::Duration;
+
+static TOAST_WIDTH: f32 = 260.;
+static TOAST_SLIDE_SECONDS: f32 = 0.3;
+
+pub struct AchievementsPlugin;
+
+impl Plugin for AchievementsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<AchievementUnlocked>()
+            .insert_resource(RunProgress::default())
+            .add_systems(
+                FixedUpdate,
+                (
+                    reset_run_progress,
+                    track_landings,
+                    track_close_calls,
+                    check_achievements,
+                )
+                    .chain()
+                    .run_if(in_state(PauseState::Running))
+                    .run_if(not(resource_equals(GameMode::Playtest))),
+            )
+            .add_systems(Update, (show_toasts, slide_toasts).chain())
+            .insert_resource(
+                Persistent::<UnlockedAchievements>::builder()
+                    .name("achievements")
+                    .format(StorageFormat::Json)
+                    .path(get_state_directory().join("achievements.json"))
+                    .default(UnlockedAchievements::default())
+                    .revert_to_default_on_deserialization_errors(true)
+                    .build()
+                    .expect("Failed to initialize achievements"),
+            );
+    }
+}
+
+/// What has to happen for an achievement to unlock.
+pub enum Condition {
+    /// Climb to at least this height in a single run.
+    ReachHeight(f32),
+    /// Reach this many new platforms in a single run.
+    PlatformsReached(u32),
+    /// Land on this many hanging platforms without touching anything else in between.
+    HangingStreak(u32),
+    /// Stay alive this long while within `distance` of the game over line.
+    CloseCall { seconds: f32, distance: f32 },
+    /// Jump this many times in a single run.
+    Jumps(u32),
+    /// Finish this many games in total.
+    GamesPlayed(u32),
+}
+
+pub struct Achievement {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub condition: Condition,
+}
+
+pub static ACHIEVEMENTS: &[Achievement] = &[
+    Achievement {
+        id: "height_1000",
+        name: "Up and away",
+        description: "Reach height 1000",
+        condition: Condition::ReachHeight(1000.),
+    },
+    Achievement {
+        id: "height_5000",
+        name: "Sky high",
+        description: "Reach height 5000",
+        condition: Condition::ReachHeight(5000.),
+    },
+    Achievement {
+        id: "platforms_50",
+        name: "Climber",
+        description: "Reach 50 platforms in one run",
+        condition: Condition::PlatformsReached(50),
+    },
+    Achievement {
+        id: "hanging_streak_2",
+        name: "Swinger",
+        description: "Land on 2 hanging platforms in a row",
+        condition: Condition::HangingStreak(2),
+    },
+    Achievement {
+        id: "close_call_5",
+        name: "Close call",
+        description: "Survive 5 seconds right above the line",
+        condition: Condition::CloseCall {
+            seconds: 5.,
+            distance: 20.,
+        },
+    },
+    Achievement {
+        id: "jumps_100",
+        name: "Hopper",
+        description: "Jump 100 times in one run",
+        condition: Condition::Jumps(100),
+    },
+    Achievement {
+        id: "games_10",
+        name: "Regular",
+        description: "Play 10 games",
+        condition: Condition::GamesPlayed(10),
+    },
+];
+
+#[derive(Event)]
+pub struct AchievementUnlocked(pub &'static Achievement);
+
+#[derive(Default, Resource, Serialize, Deserialize)]
+pub struct UnlockedAchievements(pub BTreeSet<String>);
+
+/// Per run bookkeeping for conditions that depend on more than the current frame.
+#[derive(Default, Resource)]
+struct RunProgress {
+    platforms_reached: u32,
+    hanging_streak: u32,
+    /// Time spent continuously within range of the line, per close call achievement.
+    close_calls: HashMap<&'static str, Duration>,
+}
+
+#[derive(Component)]
+struct Toast {
+    timer: Timer,
+}
+
+impl Achievement {
+    fn is_met(&self, run: &Run, progress: &RunProgress, stats: &Stats) -> bool {
+        match self.condition {
+            Condition::ReachHeight(height) => run.max_height >= height,
+            Condition::PlatformsReached(count) => progress.platforms_reached >= count,
+            Condition::HangingStreak(count) => progress.hanging_streak >= count,
+            Condition::CloseCall { seconds, .. } => progress
+                .close_calls
+                .get(self.id)
+                .is_some_and(|time| time.as_secs_f32() >= seconds),
+            Condition::Jumps(count) => run.jumps >= count,
+            Condition::GamesPlayed(count) => stats.games_played >= count,
+        }
+    }
+}
+
+fn reset_run_progress(
+    mut run_started_events: EventReader<RunStarted>,
+    mut progress: ResMut<RunProgress>,
+) {
+    if run_started_events.read().count() > 0 {
+        *progress = RunProgress::default();
+    }
+}
+
+fn track_landings(
+    mut landed_events: EventReader<Landed>,
+    mut platform_reached_events: EventReader<PlatformReached>,
+    query_platform: Query<&Platform>,
+    mut progress: ResMut<RunProgress>,
+) {
+    progress.platforms_reached += platform_reached_events.read().count() as u32;
+
+    for landed in landed_events.read() {
+        if let Ok(Platform::Hanging { .. }) = query_platform.get(landed.platform) {
+            progress.hanging_streak += 1;
+        } else {
+            progress.hanging_streak = 0;
+        }
+    }
+}
+
+fn track_close_calls(
+    time: Res<Time>,
+    height: Res<Height>,
+    query_player: Query<&Transform, With<CenterPlayer>>,
+    mut progress: ResMut<RunProgress>,
+) {
+    let Ok(transform) = query_player.get_single() else {
+        return;
+    };
+    let line_distance = transform.translation.y - (height.0 - HALF_WORLD_SIZE);
+
+    for achievement in ACHIEVEMENTS.iter() {
+        if let Condition::CloseCall { distance, .. } = achievement.condition {
+            let close_call = progress.close_calls.entry(achievement.id).or_default();
+            if line_distance <= distance {
+                *close_call += time.delta();
+            } else {
+                *close_call = Duration::ZERO;
+            }
+        }
+    }
+}
+
+fn check_achievements(
+    run: Res<Run>,
+    progress: Res<RunProgress>,
+    stats: Res<Persistent<Stats>>,
+    mut unlocked: ResMut<Persistent<UnlockedAchievements>>,
+    mut unlocked_events: EventWriter<AchievementUnlocked>,
+) {
+    let mut changed = false;
+    for achievement in ACHIEVEMENTS.iter() {
+        if !unlocked.0.contains(achievement.id) && achievement.is_met(&run, &progress, &stats) {
+            unlocked.0.insert(achievement.id.to_owned());
+            unlocked_events.send(AchievementUnlocked(achievement));
+            changed = true;
+        }
+    }
+
+    if changed {
+        unlocked
+            .persist()
+            .unwrap_or_else(|e| println!("Failed to persist achievements: {}", e));
+    }
+}
+
+fn show_toasts(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    mut unlocked_events: EventReader<AchievementUnlocked>,
+    query_toasts: Query<(), With<Toast>>,
+) {
+    let first_slot = query_toasts.iter().count();
+    for (slot, AchievementUnlocked(achievement)) in (first_slot..).zip(unlocked_events.read()) {
+        commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        top: Val::Px(10. + slot as f32 * 70.),
+                        right: Val::Px(-TOAST_WIDTH),
+                        width: Val::Px(TOAST_WIDTH),
+                        padding: UiRect::all(Val::Px(10.)),
+                        flex_direction: FlexDirection::Column,
+                        ..default()
+                    },
+                    background_color: Color::srgba(0., 0., 0., 0.7).into(),
+                    ..default()
+                },
+                Toast {
+                    timer: Timer::from_seconds(4., TimerMode::Once),
+                },
+            ))
+            .with_children(|parent| {
+                parent.spawn(text(&fonts, achievement.name, 20., TITLE_COLOR));
+                parent.spawn(text(&fonts, achievement.description, 14., TEXT_COLOR));
+            });
+    }
+}
+
+fn slide_toasts(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query_toasts: Query<(Entity, &mut Toast, &mut Style)>,
+) {
+    for (entity, mut toast, mut style) in query_toasts.iter_mut() {
+        toast.timer.tick(time.delta());
+        if toast.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        let shown = toast
+            .timer
+            .elapsed_secs()
+            .min(toast.timer.remaining_secs())
+            .min(TOAST_SLIDE_SECONDS)
+            / TOAST_SLIDE_SECONDS;
+        style.right = Val::Px(-TOAST_WIDTH + shown * (TOAST_WIDTH + 10.));
+    }
+}
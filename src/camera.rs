@@ -1,17 +1,20 @@
-use crate::{GameState, Height, WORLD_SIZE};
+use crate::{GameState, Height, InRun, WORLD_SIZE};
 use bevy::app::{App, Plugin, Startup};
 use bevy::core_pipeline::bloom::BloomSettings;
 use bevy::core_pipeline::tonemapping::Tonemapping;
 use bevy::prelude::{default, in_state, Camera, Camera2dBundle, Commands, IntoSystemConfigs, OnExit, Query, Res, Transform, Update, With};
 use bevy::render::camera::ScalingMode;
 
+/// The camera's bloom, also put back when bloom is turned on again in the settings.
+pub static BLOOM: BloomSettings = BloomSettings::NATURAL;
+
 pub struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(Startup, setup)
             .add_systems(Update, camera_scroll.run_if(in_state(GameState::InGame)))
-            .add_systems(OnExit(GameState::GameOver), reset_camera_position);
+            .add_systems(OnExit(InRun), reset_camera_position);
     }
 }
 
@@ -32,7 +35,7 @@ fn setup(mut commands: Commands) {
     commands
         .spawn((
             camera,
-            BloomSettings::default(),
+            BLOOM.clone(),
         ));
 }
 
@@ -0,0 +1,351 @@
+use crate::events::Landed;
+use crate::ui::{
+    back_to_menu, despawn_screen, format_duration, screen_root, spawn_button, text, title,
+    BackButton, TITLE_COLOR,
+};
+use crate::world::path::Path;
+use crate::world::platform::{Platform, PLATFORM_WIDTH};
+use crate::{
+    get_state_directory, start_run, FontAssets, GameMode, GameState, ImageAssets, PauseState, Run,
+};
+use bevy::app::App;
+use bevy::hierarchy::BuildChildren;
+use bevy::math::Vec2;
+use bevy::prelude::{
+    default, in_state, resource_equals, Added, Changed, Color, Commands, Component, EventReader,
+    FixedUpdate, Interaction, IntoSystemConfigs, JustifyContent, NextState, NodeBundle, OnEnter,
+    OnExit, Plugin, PositionType, Query, Res, ResMut, Resource, Sprite, Style, Update, Val, With,
+};
+use bevy_persistent::{Persistent, StorageFormat};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+pub struct ChallengePlugin;
+
+impl Plugin for ChallengePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(CurrentTower(0))
+            .add_systems(OnEnter(GameState::TowerSelect), create_tower_select_screen)
+            .add_systems(
+                OnExit(GameState::TowerSelect),
+                despawn_screen::<TowerSelectScreen>,
+            )
+            .add_systems(
+                Update,
+                (back_to_menu, select_tower).run_if(in_state(GameState::TowerSelect)),
+            )
+            .add_systems(
+                OnEnter(GameState::InGame),
+                create_tower
+                    .after(start_run)
+                    .run_if(resource_equals(GameMode::Challenge)),
+            )
+            .add_systems(
+                FixedUpdate,
+                detect_goal
+                    .run_if(resource_equals(GameMode::Challenge))
+                    .run_if(in_state(PauseState::Running)),
+            )
+            .add_systems(Update, highlight_goal)
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                (record_tower, create_tower_result)
+                    .chain()
+                    .run_if(resource_equals(GameMode::Challenge)),
+            )
+            .add_systems(OnExit(GameState::GameOver), despawn_screen::<TowerResult>)
+            .insert_resource(
+                Persistent::<TowerRecords>::builder()
+                    .name("towers")
+                    .format(StorageFormat::Json)
+                    .path(get_state_directory().join("towers.json"))
+                    .default(TowerRecords::default())
+                    .revert_to_default_on_deserialization_errors(true)
+                    .build()
+                    .expect("Failed to initialize tower records"),
+            );
+    }
+}
+
+/// A handcrafted, finite level, climbed from the ground to the goal platform at its top.
+pub struct Tower {
+    pub id: &'static str,
+    pub name: &'static str,
+    /// Finishing within this time earns a star.
+    pub par_time: Duration,
+    /// Finishing within this many jumps earns a star.
+    pub par_jumps: u32,
+    pub platforms: &'static [(Platform, Vec2)],
+    pub goal: Vec2,
+}
+
+const fn static_platform(x: f32, y: f32) -> (Platform, Vec2) {
+    (
+        Platform::Static {
+            width: PLATFORM_WIDTH,
+        },
+        Vec2::new(x, y),
+    )
+}
+
+const fn hanging_platform(x: f32, y: f32) -> (Platform, Vec2) {
+    (
+        Platform::Hanging {
+            width: PLATFORM_WIDTH,
+        },
+        Vec2::new(x, y),
+    )
+}
+
+/// A platform sliding `extent` to either side of `x`, once back and forth every `period` seconds.
+const fn sliding_platform(
+    x: f32,
+    y: f32,
+    extent: f32,
+    period: f32,
+    phase: f32,
+) -> (Platform, Vec2) {
+    (
+        Platform::Moving {
+            path: Path::Horizontal { extent },
+            period,
+            phase,
+            width: PLATFORM_WIDTH,
+        },
+        Vec2::new(x, y),
+    )
+}
+
+pub static TOWERS: &[Tower] = &[
+    Tower {
+        id: "first_steps",
+        name: "First steps",
+        par_time: Duration::from_secs(30),
+        par_jumps: 10,
+        platforms: &[
+            static_platform(-100., -110.),
+            static_platform(20., -40.),
+            static_platform(130., 30.),
+            static_platform(20., 100.),
+            static_platform(-100., 170.),
+            hanging_platform(0., 240.),
+            static_platform(110., 310.),
+            static_platform(0., 380.),
+        ],
+        goal: Vec2::new(-110., 450.),
+    },
+    Tower {
+        id: "swinging",
+        name: "Swinging",
+        par_time: Duration::from_secs(45),
+        par_jumps: 12,
+        platforms: &[
+            static_platform(90., -110.),
+            hanging_platform(-20., -40.),
+            static_platform(-130., 30.),
+            hanging_platform(-20., 100.),
+            hanging_platform(100., 170.),
+            static_platform(-10., 240.),
+            hanging_platform(-120., 310.),
+            hanging_platform(0., 380.),
+            hanging_platform(120., 450.),
+            static_platform(0., 520.),
+        ],
+        goal: Vec2::new(-110., 590.),
+    },
+    Tower {
+        id: "on_the_move",
+        name: "On the move",
+        par_time: Duration::from_secs(60),
+        par_jumps: 14,
+        platforms: &[
+            static_platform(-90., -110.),
+            sliding_platform(30., -40., 50., 10.5, 0.),
+            static_platform(140., 30.),
+            sliding_platform(20., 100., 44., 7.9, 0.5),
+            hanging_platform(-110., 170.),
+            sliding_platform(0., 240., 40., 6.3, 0.25),
+            static_platform(120., 310.),
+            sliding_platform(0., 380., 44., 7.0, 0.75),
+            hanging_platform(-120., 450.),
+            static_platform(-10., 520.),
+            sliding_platform(100., 590., 40., 6.3, 0.5),
+        ],
+        goal: Vec2::new(-20., 660.),
+    },
+];
+
+/// Index into `TOWERS` of the tower being played.
+#[derive(Resource)]
+pub struct CurrentTower(pub usize);
+
+/// Best results per tower id.
+#[derive(Default, Resource, Serialize, Deserialize)]
+pub struct TowerRecords(pub BTreeMap<String, TowerRecord>);
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TowerRecord {
+    pub stars: u8,
+    pub best_time: Duration,
+    pub fewest_jumps: u32,
+}
+
+impl Tower {
+    /// One star for reaching the goal, and one each for beating the par time and par jumps.
+    fn stars(&self, run: &Run) -> u8 {
+        1 + (run.duration <= self.par_time) as u8 + (run.jumps <= self.par_jumps) as u8
+    }
+}
+
+#[derive(Component)]
+struct Goal;
+
+#[derive(Component)]
+struct TowerSelectScreen;
+
+#[derive(Component)]
+struct TowerButton(usize);
+
+#[derive(Component)]
+struct TowerResult;
+
+fn stars_text(stars: u8) -> String {
+    format!(
+        "{}{}",
+        "*".repeat(stars as usize),
+        "-".repeat(3 - stars as usize)
+    )
+}
+
+fn create_tower_select_screen(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    records: Res<Persistent<TowerRecords>>,
+) {
+    commands
+        .spawn((screen_root(), TowerSelectScreen))
+        .with_children(|parent| {
+            parent.spawn(title(&fonts, "Towers"));
+            for (index, tower) in TOWERS.iter().enumerate() {
+                let stars = records.0.get(tower.id).map(|r| r.stars).unwrap_or(0);
+                spawn_button(
+                    parent,
+                    &fonts,
+                    format!("{} {}", tower.name, stars_text(stars)),
+                    TowerButton(index),
+                );
+            }
+            spawn_button(parent, &fonts, "Back", BackButton);
+        });
+}
+
+fn select_tower(
+    query_buttons: Query<(&Interaction, &TowerButton), Changed<Interaction>>,
+    mut current_tower: ResMut<CurrentTower>,
+    mut mode: ResMut<GameMode>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for (interaction, TowerButton(index)) in query_buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            current_tower.0 = *index;
+            *mode = GameMode::Challenge;
+            next_state.set(GameState::InGame);
+        }
+    }
+}
+
+fn create_tower(
+    mut commands: Commands,
+    images: Res<ImageAssets>,
+    current_tower: Res<CurrentTower>,
+) {
+    let tower = &TOWERS[current_tower.0];
+    for (platform, pos) in tower.platforms {
+        platform.spawn(&mut commands, &images, *pos);
+    }
+    let goal = Platform::default().spawn(&mut commands, &images, tower.goal);
+    commands.entity(goal).insert(Goal);
+}
+
+fn highlight_goal(mut query_goal: Query<&mut Sprite, Added<Goal>>) {
+    for mut sprite in query_goal.iter_mut() {
+        sprite.color = Color::srgb(2., 1.6, 0.2);
+    }
+}
+
+fn detect_goal(
+    mut landed_events: EventReader<Landed>,
+    query_goal: Query<(), With<Goal>>,
+    mut run: ResMut<Run>,
+) {
+    if landed_events
+        .read()
+        .any(|landed| query_goal.contains(landed.platform))
+    {
+        run.reached_goal = true;
+    }
+}
+
+fn record_tower(
+    run: Res<Run>,
+    current_tower: Res<CurrentTower>,
+    mut records: ResMut<Persistent<TowerRecords>>,
+) {
+    if !run.reached_goal {
+        return;
+    }
+
+    let tower = &TOWERS[current_tower.0];
+    let record = records.0.entry(tower.id.to_owned()).or_insert(TowerRecord {
+        stars: 0,
+        best_time: run.duration,
+        fewest_jumps: run.jumps,
+    });
+    record.stars = record.stars.max(tower.stars(&run));
+    record.best_time = record.best_time.min(run.duration);
+    record.fewest_jumps = record.fewest_jumps.min(run.jumps);
+
+    records
+        .persist()
+        .unwrap_or_else(|e| println!("Failed to persist tower records: {}", e));
+}
+
+fn create_tower_result(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    run: Res<Run>,
+    current_tower: Res<CurrentTower>,
+) {
+    let tower = &TOWERS[current_tower.0];
+    let result = if run.reached_goal {
+        format!(
+            "{}  {} / {}  {} / {} jumps",
+            stars_text(tower.stars(&run)),
+            format_duration(run.duration),
+            format_duration(tower.par_time),
+            run.jumps,
+            tower.par_jumps,
+        )
+    } else {
+        format!("{} not reached", tower.name)
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    bottom: Val::Px(110.),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            TowerResult,
+        ))
+        .with_children(|parent| {
+            parent.spawn(text(&fonts, result, 24., TITLE_COLOR));
+        });
+}
@@ -1,7 +1,7 @@
-use crate::{GameState, Height, ImageAssets};
+use crate::{GameState, Height, ImageAssets, InRun, PauseState};
 use bevy::app::App;
 use bevy::asset::Handle;
-use bevy::prelude::{default, in_state, Bundle, Camera, Commands, Component, Entity, FixedUpdate, GlobalTransform, Image, IntoSystemConfigs, OnEnter, OnExit, Plugin, Query, Res, ResMut, Resource, SpriteBundle, Time, Timer, Transform, Update, Vec2, Vec3, Window, With};
+use bevy::prelude::{default, in_state, not, Bundle, Camera, Commands, Component, Condition, Entity, FixedUpdate, GlobalTransform, Image, IntoSystemConfigs, OnEnter, OnExit, Plugin, Query, Res, ResMut, Resource, SpriteBundle, Time, Timer, Transform, Update, Vec2, Vec3, Window, With};
 use bevy::time::TimerMode;
 use bevy::window::PrimaryWindow;
 use rand::Rng;
@@ -17,12 +17,15 @@ impl Plugin for CloudsPlugin {
             timer: Timer::from_seconds(5., TimerMode::Repeating),
         })
             .add_systems(OnEnter(GameState::InGame), add_initial_clouds)
-            .add_systems(OnExit(GameState::GameOver), remove_all_clouds)
+            .add_systems(OnExit(InRun), remove_all_clouds)
+            .add_systems(OnEnter(GameState::MainMenu), add_initial_clouds)
+            .add_systems(OnExit(GameState::MainMenu), remove_all_clouds)
         .add_systems(
             FixedUpdate,
-            (add_clouds, remove_clouds).run_if(in_state(GameState::InGame)),
+            (add_clouds, remove_clouds)
+                .run_if(in_state(PauseState::Running).or_else(in_state(GameState::MainMenu))),
         )
-        .add_systems(Update, scroll_clouds);
+        .add_systems(Update, scroll_clouds.run_if(not(in_state(PauseState::Paused))));
     }
 }
 
@@ -1,12 +1,15 @@
+use crate::settings::Settings;
+use crate::PauseState;
 use bevy::app::App;
 use bevy::input::mouse::MouseMotion;
 use bevy::input::ButtonInput;
 use bevy::math::Vec2;
 use bevy::prelude::{
-    Event, EventReader, EventWriter, KeyCode, Local, MouseButton, Plugin, Query, Res, Update,
-    Window, With,
+    in_state, Event, EventReader, EventWriter, IntoSystemConfigs, KeyCode, Local, MouseButton,
+    Plugin, Query, Res, Update, Window, With,
 };
 use bevy::window::PrimaryWindow;
+use bevy_persistent::Persistent;
 
 #[derive(Event, Default, Debug)]
 pub struct Drag {
@@ -15,12 +18,22 @@ pub struct Drag {
     pub done: bool,
 }
 
+/// A finished drag with the right mouse button, that throws whatever the blob holds.
+#[derive(Event, Debug)]
+pub struct Throw {
+    pub drag: Vec2,
+}
+
 pub struct DragPlugin;
 
 impl Plugin for DragPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, (mouse_drag, keyboard_drag))
-            .add_event::<Drag>();
+        app.add_systems(
+            Update,
+            (mouse_drag, mouse_throw, keyboard_drag).run_if(in_state(PauseState::Running)),
+        )
+        .add_event::<Drag>()
+        .add_event::<Throw>();
     }
 }
 
@@ -59,7 +72,9 @@ fn mouse_drag(
     mut query_window: Query<&mut Window, With<PrimaryWindow>>,
     mut event_writer: EventWriter<Drag>,
     mut drag_last: Local<Vec2>,
+    mut dragging: Local<bool>,
     mut evr_motion: EventReader<MouseMotion>,
+    settings: Res<Persistent<Settings>>,
 ) {
     if mouse_button.pressed(MouseButton::Left)
         || mouse_button.just_released(MouseButton::Left)
@@ -70,20 +85,22 @@ fn mouse_drag(
 
         if mouse_button.just_pressed(MouseButton::Left) {
             *drag_last = Vec2::ZERO;
+            *dragging = true;
             event_writer.send(Drag {
                 start: Vec2::ZERO,
                 end: Vec2::ZERO,
                 done: false,
             });
             window.cursor.visible = false;
-        } else if mouse_button.pressed(MouseButton::Left) {
-            *drag_last += Vec2::new(mouse_move.x, -mouse_move.y);
+        } else if *dragging && mouse_button.pressed(MouseButton::Left) {
+            *drag_last += Vec2::new(mouse_move.x, -mouse_move.y) * settings.sensitivity;
             event_writer.send(Drag {
                 start: Vec2::ZERO,
                 end: *drag_last,
                 done: false,
             });
-        } else if mouse_button.just_released(MouseButton::Left) {
+        } else if *dragging && mouse_button.just_released(MouseButton::Left) {
+            *dragging = false;
             event_writer.send(Drag {
                 start: Vec2::ZERO,
                 end: *drag_last,
@@ -93,3 +110,20 @@ fn mouse_drag(
         }
     }
 }
+
+fn mouse_throw(
+    mouse_button: Res<ButtonInput<MouseButton>>,
+    mut event_writer: EventWriter<Throw>,
+    mut drag: Local<Vec2>,
+    mut evr_motion: EventReader<MouseMotion>,
+    settings: Res<Persistent<Settings>>,
+) {
+    let mouse_move: Vec2 = evr_motion.read().map(|e| e.delta).sum();
+    if mouse_button.just_pressed(MouseButton::Right) {
+        *drag = Vec2::ZERO;
+    } else if mouse_button.pressed(MouseButton::Right) {
+        *drag += Vec2::new(mouse_move.x, -mouse_move.y) * settings.sensitivity;
+    } else if mouse_button.just_released(MouseButton::Right) {
+        event_writer.send(Throw { drag: *drag });
+    }
+}
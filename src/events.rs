@@ -0,0 +1,65 @@
+use bevy::app::App;
+use bevy::prelude::{Entity, Event, Plugin};
+use serde::{Deserialize, Serialize};
+
+/// Typed gameplay events, so scoring, stats and achievements can react to what happens in a run
+/// without the player and world systems knowing about them.
+pub struct GameplayEventsPlugin;
+
+impl Plugin for GameplayEventsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<RunStarted>()
+            .add_event::<Jumped>()
+            .add_event::<Climbed>()
+            .add_event::<Landed>()
+            .add_event::<PlatformReached>()
+            .add_event::<NewHighScore>()
+            .add_event::<Died>();
+    }
+}
+
+/// A new run has started and the player has been spawned.
+#[derive(Event)]
+pub struct RunStarted;
+
+/// The blob jumped off.
+#[derive(Event)]
+pub struct Jumped;
+
+/// The blob climbed higher than ever before in this run.
+#[derive(Event)]
+pub struct Climbed {
+    pub height: f32,
+}
+
+/// The blob touched down on a platform other than the one it last stood on.
+#[derive(Event)]
+pub struct Landed {
+    pub platform: Entity,
+}
+
+/// The blob landed on a platform higher than any it has been on before in this run.
+#[derive(Event)]
+pub struct PlatformReached {
+    pub platform: Entity,
+    /// Height of the platform's top.
+    pub height: f32,
+}
+
+/// The score of the current run passed the best score on the leaderboard.
+#[derive(Event)]
+pub struct NewHighScore;
+
+/// The blob left the playable area and the run is over.
+#[derive(Event)]
+pub struct Died {
+    pub cause: DeathCause,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum DeathCause {
+    /// Dropped below the rising game over line.
+    FellBelowLine,
+    /// Left the sides of the world.
+    OutOfBounds,
+}
@@ -0,0 +1,244 @@
+use crate::score::Score;
+use crate::ui::{
+    back_to_menu, despawn_screen, format_duration, screen_root, spawn_button, text, title,
+    BackButton, TEXT_COLOR, TITLE_COLOR,
+};
+use crate::{get_state_directory, FontAssets, GameMode, GameState, Run};
+use bevy::app::App;
+use bevy::hierarchy::BuildChildren;
+use bevy::prelude::{
+    in_state, resource_changed, Changed, Commands, Component, Interaction, IntoSystemConfigs,
+    OnEnter, OnExit, Plugin, Query, Res, ResMut, Resource, Update, With,
+};
+use bevy_persistent::{Persistent, StorageFormat};
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+static LEADERBOARD_SIZE: usize = 20;
+
+pub struct LeaderboardPlugin;
+
+impl Plugin for LeaderboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(LatestRank(None))
+            .insert_resource(ShownMode(GameMode::default()))
+            .add_systems(OnEnter(GameState::GameOver), record_run)
+            .add_systems(
+                OnEnter(GameState::Leaderboard),
+                (show_current_mode, create_leaderboard_screen).chain(),
+            )
+            .add_systems(
+                OnExit(GameState::Leaderboard),
+                despawn_screen::<LeaderboardScreen>,
+            )
+            .add_systems(
+                Update,
+                (
+                    back_to_menu,
+                    (
+                        change_leaderboard_mode,
+                        (
+                            despawn_screen::<LeaderboardScreen>,
+                            create_leaderboard_screen,
+                        )
+                            .chain()
+                            .run_if(resource_changed::<ShownMode>),
+                    )
+                        .chain(),
+                )
+                    .run_if(in_state(GameState::Leaderboard)),
+            )
+            .insert_resource(
+                Persistent::<Leaderboard>::builder()
+                    .name("leaderboard")
+                    .format(StorageFormat::Json)
+                    .path(get_state_directory().join("leaderboard.json"))
+                    .default(Leaderboard::default())
+                    .revert_to_default_on_deserialization_errors(true)
+                    .build()
+                    .expect("Failed to initialize leaderboard"),
+            );
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub score: u32,
+    pub max_height: f32,
+    pub date: DateTime<Utc>,
+    pub duration: Duration,
+    pub seed: u64,
+    pub mode: GameMode,
+    pub name: Option<String>,
+}
+
+impl LeaderboardEntry {
+    /// Whether this entry ranks at least as well as `other`, in the way their mode is ranked.
+    fn ranks_ahead_of(&self, other: &LeaderboardEntry) -> bool {
+        if self.mode.ranked_by_time() {
+            self.duration <= other.duration
+        } else {
+            self.score >= other.score
+        }
+    }
+
+    pub fn summary(&self, rank: usize) -> String {
+        format!(
+            "{:>2}. {:>6} {:>6.0}m {:>6} {} {}",
+            rank + 1,
+            self.score,
+            self.max_height.max(0.),
+            format_duration(self.duration),
+            self.date.with_timezone(&Local).format("%Y-%m-%d"),
+            self.name.as_deref().unwrap_or(""),
+        )
+    }
+}
+
+/// The best runs of every mode, each mode's entries in rank order.
+#[derive(Default, Resource, Serialize, Deserialize)]
+pub struct Leaderboard {
+    entries: Vec<LeaderboardEntry>,
+}
+
+impl Leaderboard {
+    pub fn entries(&self, mode: GameMode) -> impl Iterator<Item = &LeaderboardEntry> {
+        self.entries.iter().filter(move |e| e.mode == mode)
+    }
+
+    /// Inserts the entry at its rank, returning the rank if the entry made it onto its mode's board.
+    fn insert(&mut self, entry: LeaderboardEntry) -> Option<usize> {
+        let mode = entry.mode;
+        let rank = self
+            .entries(mode)
+            .filter(|e| e.ranks_ahead_of(&entry))
+            .count();
+        if rank >= LEADERBOARD_SIZE {
+            return None;
+        }
+        let index = self
+            .entries
+            .iter()
+            .position(|e| e.mode == mode && !e.ranks_ahead_of(&entry))
+            .unwrap_or(self.entries.len());
+        self.entries.insert(index, entry);
+
+        if let Some((index, _)) = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| e.mode == mode)
+            .nth(LEADERBOARD_SIZE)
+        {
+            self.entries.remove(index);
+        }
+        Some(rank)
+    }
+
+    pub fn best(&self, mode: GameMode) -> Option<&LeaderboardEntry> {
+        self.entries(mode).next()
+    }
+
+    pub fn high_score(&self, mode: GameMode) -> u32 {
+        self.best(mode).map(|e| e.score).unwrap_or(0)
+    }
+}
+
+/// Mode and rank of the most recently finished run, if it made it onto the leaderboard.
+#[derive(Resource)]
+pub struct LatestRank(pub Option<(GameMode, usize)>);
+
+#[derive(Component)]
+struct LeaderboardScreen;
+
+/// The mode whose board is shown, starting out at the mode last played.
+#[derive(Resource)]
+struct ShownMode(GameMode);
+
+/// Switches the board shown to the next mode.
+#[derive(Component)]
+struct LeaderboardModeButton;
+
+pub fn record_run(
+    score: Res<Score>,
+    run: Res<Run>,
+    mode: Res<GameMode>,
+    mut leaderboard: ResMut<Persistent<Leaderboard>>,
+    mut latest_rank: ResMut<LatestRank>,
+) {
+    // Only runs that made it to the goal have a time worth ranking, and towers keep their own
+    // records.
+    if *mode == GameMode::Challenge || (mode.ranked_by_time() && !mode.is_finished(&run)) {
+        latest_rank.0 = None;
+        return;
+    }
+
+    latest_rank.0 = leaderboard
+        .insert(LeaderboardEntry {
+            score: score.0,
+            max_height: run.max_height,
+            date: Utc::now(),
+            duration: run.duration,
+            seed: run.seed,
+            mode: *mode,
+            name: None,
+        })
+        .map(|rank| (*mode, rank));
+
+    leaderboard
+        .persist()
+        .unwrap_or_else(|e| println!("Failed to persist leaderboard: {}", e));
+}
+
+fn show_current_mode(mode: Res<GameMode>, mut shown_mode: ResMut<ShownMode>) {
+    shown_mode.0 = *mode;
+}
+
+fn create_leaderboard_screen(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    leaderboard: Res<Persistent<Leaderboard>>,
+    latest_rank: Res<LatestRank>,
+    shown_mode: Res<ShownMode>,
+) {
+    let mode = &shown_mode.0;
+    commands
+        .spawn((screen_root(), LeaderboardScreen))
+        .with_children(|parent| {
+            parent.spawn(title(&fonts, "Leaderboard"));
+            spawn_button(
+                parent,
+                &fonts,
+                format!("Mode: {}", mode.name()),
+                LeaderboardModeButton,
+            );
+
+            if leaderboard.best(*mode).is_none() {
+                parent.spawn(text(&fonts, "No runs yet", 20., TEXT_COLOR));
+            }
+
+            for (rank, entry) in leaderboard.entries(*mode).enumerate() {
+                let color = if latest_rank.0 == Some((*mode, rank)) {
+                    TITLE_COLOR
+                } else {
+                    TEXT_COLOR
+                };
+                parent.spawn(text(&fonts, entry.summary(rank), 20., color));
+            }
+
+            spawn_button(parent, &fonts, "Back", BackButton);
+        });
+}
+
+fn change_leaderboard_mode(
+    query_buttons: Query<&Interaction, (Changed<Interaction>, With<LeaderboardModeButton>)>,
+    mut shown_mode: ResMut<ShownMode>,
+) {
+    if query_buttons
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed)
+    {
+        shown_mode.0 = shown_mode.0.next();
+    }
+}
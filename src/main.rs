@@ -1,17 +1,40 @@
+mod achievements;
 mod camera;
+mod challenge;
 mod clouds;
 mod drag;
+mod events;
 mod game_over_line;
+mod leaderboard;
+mod menu;
+mod pause;
 mod world;
 mod player;
+mod powerup;
 mod score;
+mod settings;
+mod shop;
+mod stats;
+mod ui;
 
+use crate::achievements::AchievementsPlugin;
 use crate::camera::CameraPlugin;
+use crate::challenge::ChallengePlugin;
 use crate::clouds::CloudsPlugin;
 use crate::drag::DragPlugin;
+use crate::events::{Climbed, Died, GameplayEventsPlugin, Jumped};
 use crate::game_over_line::GameOverLinePlugin;
+use crate::leaderboard::LeaderboardPlugin;
+use crate::menu::MenuPlugin;
+use crate::pause::PausePlugin;
 use crate::player::PlayerPlugin;
+use crate::powerup::PowerUpsPlugin;
 use crate::score::ScorePlugin;
+use crate::settings::SettingsPlugin;
+use crate::shop::ShopPlugin;
+use crate::stats::StatsPlugin;
+use crate::ui::highlight_buttons;
+use crate::world::level::Level;
 use crate::world::WorldPlugin;
 use avian2d::prelude::*;
 use bevy::prelude::*;
@@ -23,26 +46,153 @@ use bevy_asset_loader::prelude::{
 };
 use bevy_persistent::prelude::*;
 use bevy_persistent_windows::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use std::time::Duration;
 
 static WORLD_SIZE: f32 = 400.;
 static HALF_WORLD_SIZE: f32 = WORLD_SIZE / 2.;
+static TIME_ATTACK_HEIGHT: f32 = 3000.;
+static SPRINT_DURATION: Duration = Duration::from_secs(60);
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, States)]
 enum GameState {
     #[default]
     Loading,
+    MainMenu,
     InGame,
     GameOver,
+    /// Passes straight on to `InGame`, so a new run can start from within a run.
+    Restarting,
+    Leaderboard,
+    Stats,
+    TowerSelect,
+    Shop,
+    Editor,
+}
+
+#[derive(SubStates, Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+#[source(GameState = GameState::InGame)]
+enum PauseState {
+    #[default]
+    Running,
+    Paused,
+}
+
+/// Active while a run's world is around, from starting to play until leaving the game over screen.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+struct InRun;
+
+impl ComputedStates for InRun {
+    type SourceStates = GameState;
+
+    fn compute(sources: GameState) -> Option<Self> {
+        matches!(sources, GameState::InGame | GameState::GameOver).then_some(InRun)
+    }
+}
+
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Default, Debug, Serialize, Deserialize)]
+enum GameMode {
+    /// Scored by platforms reached, with combo, flip and risk bonuses.
+    #[default]
+    Endless,
+    /// Scored by height alone.
+    Classic,
+    /// The line only follows the camera and never rises on its own.
+    Zen,
+    /// Reach `TIME_ATTACK_HEIGHT` as fast as possible.
+    TimeAttack,
+    /// A faster line, and no glow showing when the blob can jump again.
+    Hardcore,
+    /// Climb as high as possible within `SPRINT_DURATION`.
+    Sprint,
+    /// Climb one of the handcrafted towers to its goal. Picked from the tower select screen.
+    Challenge,
+    /// Try out the level being edited, returning to the editor when the run ends.
+    Playtest,
+}
+
+impl GameMode {
+    fn name(&self) -> &'static str {
+        match self {
+            GameMode::Endless => "Endless",
+            GameMode::Classic => "Classic",
+            GameMode::Zen => "Zen",
+            GameMode::TimeAttack => "Time Attack",
+            GameMode::Hardcore => "Hardcore",
+            GameMode::Sprint => "Sprint",
+            GameMode::Challenge => "Challenge",
+            GameMode::Playtest => "Playtest",
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            GameMode::Endless => GameMode::Classic,
+            GameMode::Classic => GameMode::Zen,
+            GameMode::Zen => GameMode::TimeAttack,
+            GameMode::TimeAttack => GameMode::Hardcore,
+            GameMode::Hardcore => GameMode::Sprint,
+            GameMode::Sprint | GameMode::Challenge | GameMode::Playtest => GameMode::Endless,
+        }
+    }
+
+    fn scored_by_height(self) -> bool {
+        matches!(
+            self,
+            GameMode::Classic | GameMode::TimeAttack | GameMode::Sprint
+        )
+    }
+
+    /// Whether the world is generated as the blob climbs, rather than laid out beforehand.
+    fn is_procedural(self) -> bool {
+        !matches!(self, GameMode::Challenge | GameMode::Playtest)
+    }
+
+    /// Whether runs are ranked by how fast they were rather than by score.
+    fn ranked_by_time(self) -> bool {
+        matches!(self, GameMode::TimeAttack | GameMode::Challenge)
+    }
+
+    /// How fast the line rises on its own, in units per second.
+    fn line_speed(self) -> f32 {
+        match self {
+            GameMode::Zen | GameMode::Challenge | GameMode::Playtest => 0.,
+            GameMode::Hardcore => 30.,
+            _ => 15.,
+        }
+    }
+
+    /// Whether the run has reached the mode's goal and is over without the blob dying.
+    fn is_finished(self, run: &Run) -> bool {
+        match self {
+            GameMode::TimeAttack => run.max_height >= TIME_ATTACK_HEIGHT,
+            GameMode::Sprint => run.duration >= SPRINT_DURATION,
+            GameMode::Challenge => run.reached_goal,
+            _ => false,
+        }
+    }
 }
 
 #[derive(AssetCollection, Resource)]
 struct ImageAssets {
     #[asset(
-        paths("images/platform1.png", "images/platform2.png", "images/platform3.png"),
+        paths(
+            "images/platform1.png",
+            "images/platform2.png",
+            "images/platform3.png",
+            "images/platform4.png",
+            "images/platform5.png"
+        ),
         collection(typed)
     )]
     platforms: Vec<Handle<Image>>,
+
+    #[asset(
+        paths("images/platform6.png", "images/platform7.png", "images/platform8.png"),
+        collection(typed)
+    )]
+    surfaces: Vec<Handle<Image>>,
     
     #[asset(
         paths(
@@ -59,6 +209,24 @@ struct ImageAssets {
     #[asset(path = "images/bolt.png")]
     bolt: Handle<Image>,
 
+    #[asset(path = "images/rope.png")]
+    rope: Handle<Image>,
+
+    #[asset(path = "images/coin.png")]
+    coin: Handle<Image>,
+
+    #[asset(
+        paths(
+            "images/powerup1.png",
+            "images/powerup2.png",
+            "images/powerup3.png",
+            "images/powerup4.png",
+            "images/powerup5.png"
+        ),
+        collection(typed)
+    )]
+    power_ups: Vec<Handle<Image>>,
+
     #[asset(
         paths("images/box1.png", "images/box2.png", "images/box3.png"),
         collection(typed)
@@ -72,6 +240,20 @@ struct FontAssets {
     segmental: Handle<Font>,
 }
 
+#[derive(AssetCollection, Resource)]
+struct LevelAssets {
+    /// Handcrafted chunks spliced in between the randomly generated platforms.
+    #[asset(
+        paths(
+            "levels/chunks/stairs.level.ron",
+            "levels/chunks/swings.level.ron",
+            "levels/chunks/carousel.level.ron"
+        ),
+        collection(typed)
+    )]
+    chunks: Vec<Handle<Level>>,
+}
+
 #[derive(Resource)]
 struct MaterialHandles {
     black: Handle<ColorMaterial>,
@@ -89,6 +271,16 @@ struct MeshHandles {
 #[derive(Resource)]
 struct Height(f32);
 
+#[derive(Resource, Default)]
+struct Run {
+    seed: u64,
+    duration: Duration,
+    max_height: f32,
+    jumps: u32,
+    /// Landed on the goal platform of a challenge tower.
+    reached_goal: bool,
+}
+
 fn main() {
     let mut app = App::new();
 
@@ -130,31 +322,51 @@ fn main() {
     app.add_plugins((
         PersistentWindowsPlugin,
         PhysicsPlugins::default().with_length_unit(100.0),
+        GameplayEventsPlugin,
         DragPlugin,
         PlayerPlugin,
         CameraPlugin,
         WorldPlugin,
         GameOverLinePlugin,
         ScorePlugin,
+    ))
+    .add_plugins((
+        LeaderboardPlugin,
+        StatsPlugin,
+        PausePlugin,
+        MenuPlugin,
+        SettingsPlugin,
+        ChallengePlugin,
+        AchievementsPlugin,
         CloudsPlugin,
+        ShopPlugin,
+        PowerUpsPlugin,
     ))
     .init_state::<GameState>()
+    .add_sub_state::<PauseState>()
+    .add_computed_state::<InRun>()
     .add_loading_state(
         LoadingState::new(GameState::Loading)
-            .continue_to_state(GameState::InGame)
+            .continue_to_state(GameState::MainMenu)
             .load_collection::<ImageAssets>()
-            .load_collection::<FontAssets>(),
+            .load_collection::<FontAssets>()
+            .load_collection::<LevelAssets>(),
     )
     .add_systems(Startup, setup)
+    .add_systems(OnEnter(GameState::InGame), start_run)
     .add_systems(
         FixedUpdate,
-        increase_height.run_if(in_state(GameState::InGame)),
+        (track_run, increase_height, end_run).run_if(in_state(PauseState::Running)),
     )
-    .add_systems(Update, restart_game.run_if(in_state(GameState::GameOver)))
-    .add_systems(OnExit(GameState::GameOver), cleanup_game)
+    .add_systems(Update, tick_run.run_if(in_state(PauseState::Running)))
+    .add_systems(Update, highlight_buttons)
+    .add_systems(OnEnter(GameState::Restarting), start_again)
+    .add_systems(OnExit(InRun), cleanup_game)
     .insert_resource(SubstepCount(6))
     .insert_resource(Gravity(Vec2::NEG_Y * 981.0))
     .insert_resource(Height(0.0))
+    .insert_resource(Run::default())
+    .insert_resource(GameMode::default())
     .insert_resource(ClearColor(Color::srgb(0.46, 0.58, 1.0)))
     .run();
 }
@@ -176,9 +388,50 @@ fn setup(
     });
 }
 
-fn increase_height(time: Res<Time>, mut height: ResMut<Height>) {
+fn start_run(mut run: ResMut<Run>) {
+    *run = Run {
+        seed: rand::random(),
+        ..default()
+    };
+}
+
+fn tick_run(time: Res<Time>, mut run: ResMut<Run>) {
+    run.duration += time.delta();
+}
+
+fn track_run(
+    mut climbed_events: EventReader<Climbed>,
+    mut jumped_events: EventReader<Jumped>,
+    mut run: ResMut<Run>,
+    mut height: ResMut<Height>,
+) {
+    for climbed in climbed_events.read() {
+        run.max_height = run.max_height.max(climbed.height);
+        if climbed.height > height.0 + 50. {
+            height.0 = climbed.height;
+        }
+    }
+    run.jumps += jumped_events.read().count() as u32;
+}
+
+fn end_run(
+    mode: Res<GameMode>,
+    run: Res<Run>,
+    mut died_events: EventReader<Died>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if died_events.read().next().is_some() || mode.is_finished(&run) {
+        next_state.set(if *mode == GameMode::Playtest {
+            GameState::Editor
+        } else {
+            GameState::GameOver
+        });
+    }
+}
+
+fn increase_height(time: Res<Time>, mode: Res<GameMode>, mut height: ResMut<Height>) {
     if height.0 > 50. {
-        height.0 += time.delta_seconds() * 15.0;
+        height.0 += time.delta_seconds() * mode.line_speed();
     }
 }
 
@@ -194,14 +447,8 @@ fn cleanup_game(
     }
 }
 
-fn restart_game(
-    mut next_state: ResMut<NextState<GameState>>,
-    keys: Res<ButtonInput<KeyCode>>,
-    buttons: Res<ButtonInput<MouseButton>>,
-) {
-    if keys.get_just_pressed().len() > 0 || buttons.get_just_pressed().len() > 0 {
-        next_state.set(GameState::InGame);
-    }
+fn start_again(mut next_state: ResMut<NextState<GameState>>) {
+    next_state.set(GameState::InGame);
 }
 
 fn get_state_directory() -> PathBuf {
@@ -0,0 +1,104 @@
+use crate::settings::SettingsState;
+use crate::ui::{despawn_screen, screen_root, spawn_button, text, TITLE_COLOR};
+use crate::{FontAssets, GameMode, GameState};
+use bevy::app::{App, AppExit};
+use bevy::hierarchy::{BuildChildren, Children};
+use bevy::prelude::{
+    default, in_state, Changed, Color, Commands, Component, EventWriter, Interaction,
+    IntoSystemConfigs, NextState, OnEnter, OnExit, Plugin, Query, Res, ResMut, Style, Text, UiRect,
+    Update, Val,
+};
+
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::MainMenu), create_main_menu)
+            .add_systems(OnExit(GameState::MainMenu), despawn_screen::<MainMenu>)
+            .add_systems(
+                Update,
+                main_menu_action.run_if(in_state(GameState::MainMenu)),
+            );
+    }
+}
+
+#[derive(Component)]
+struct MainMenu;
+
+#[derive(Component)]
+enum MenuButton {
+    Play,
+    Mode,
+    Towers,
+    Leaderboard,
+    Stats,
+    Shop,
+    Editor,
+    Settings,
+    Quit,
+}
+
+fn mode_label(mode: GameMode) -> String {
+    format!("Mode: {}", mode.name())
+}
+
+fn create_main_menu(mut commands: Commands, fonts: Res<FontAssets>, mut mode: ResMut<GameMode>) {
+    // Towers and playtests are only played from their own screens.
+    if !mode.is_procedural() {
+        *mode = GameMode::default();
+    }
+
+    let mut root = screen_root();
+    // Let the clouds drifting by behind the menu show through.
+    root.background_color = Color::NONE.into();
+
+    commands.spawn((root, MainMenu)).with_children(|parent| {
+        parent.spawn(text(&fonts, "Hopp!", 80., TITLE_COLOR).with_style(Style {
+            margin: UiRect::bottom(Val::Px(40.)),
+            ..default()
+        }));
+        spawn_button(parent, &fonts, "Play", MenuButton::Play);
+        spawn_button(parent, &fonts, mode_label(*mode), MenuButton::Mode);
+        spawn_button(parent, &fonts, "Towers", MenuButton::Towers);
+        spawn_button(parent, &fonts, "Leaderboard", MenuButton::Leaderboard);
+        spawn_button(parent, &fonts, "Stats", MenuButton::Stats);
+        spawn_button(parent, &fonts, "Shop", MenuButton::Shop);
+        spawn_button(parent, &fonts, "Editor", MenuButton::Editor);
+        spawn_button(parent, &fonts, "Settings", MenuButton::Settings);
+        spawn_button(parent, &fonts, "Quit", MenuButton::Quit);
+    });
+}
+
+fn main_menu_action(
+    query_buttons: Query<(&Interaction, &MenuButton, &Children), Changed<Interaction>>,
+    mut query_text: Query<&mut Text>,
+    mut mode: ResMut<GameMode>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut next_settings_state: ResMut<NextState<SettingsState>>,
+    mut exit_events: EventWriter<AppExit>,
+) {
+    for (interaction, button, children) in query_buttons.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match button {
+            MenuButton::Play => next_state.set(GameState::InGame),
+            MenuButton::Mode => {
+                *mode = mode.next();
+                let mut iter = query_text.iter_many_mut(children);
+                while let Some(mut text) = iter.fetch_next() {
+                    text.sections[0].value = mode_label(*mode);
+                }
+            }
+            MenuButton::Towers => next_state.set(GameState::TowerSelect),
+            MenuButton::Leaderboard => next_state.set(GameState::Leaderboard),
+            MenuButton::Stats => next_state.set(GameState::Stats),
+            MenuButton::Shop => next_state.set(GameState::Shop),
+            MenuButton::Editor => next_state.set(GameState::Editor),
+            MenuButton::Settings => next_settings_state.set(SettingsState::Open),
+            MenuButton::Quit => {
+                exit_events.send(AppExit::Success);
+            }
+        }
+    }
+}
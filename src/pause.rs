@@ -0,0 +1,115 @@
+use crate::settings::SettingsState;
+use crate::ui::{despawn_screen, screen_root, spawn_button, title};
+use crate::{FontAssets, GameState, PauseState};
+use avian2d::prelude::{Physics, PhysicsTime};
+use bevy::app::App;
+use bevy::hierarchy::BuildChildren;
+use bevy::input::ButtonInput;
+use bevy::prelude::{
+    in_state, Changed, Commands, Component, EventReader, Interaction, IntoSystemConfigs, KeyCode,
+    NextState, OnEnter, OnExit, Plugin, Query, Res, ResMut, State, Time, Update, Window, With,
+};
+use bevy::window::{PrimaryWindow, WindowFocused};
+
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                toggle_pause.run_if(in_state(SettingsState::Closed)),
+                pause_on_focus_lost,
+            )
+                .run_if(in_state(GameState::InGame)),
+        )
+        .add_systems(
+            Update,
+            pause_menu_action.run_if(in_state(PauseState::Paused)),
+        )
+        .add_systems(OnEnter(PauseState::Paused), (pause_game, create_pause_menu))
+        .add_systems(
+            OnExit(PauseState::Paused),
+            (resume_game, despawn_screen::<PauseMenu>),
+        );
+    }
+}
+
+#[derive(Component)]
+struct PauseMenu;
+
+#[derive(Component)]
+enum PauseButton {
+    Resume,
+    Restart,
+    Settings,
+    Quit,
+}
+
+fn toggle_pause(
+    keys: Res<ButtonInput<KeyCode>>,
+    pause_state: Res<State<PauseState>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+) {
+    if keys.just_pressed(KeyCode::Escape) {
+        next_pause_state.set(match pause_state.get() {
+            PauseState::Running => PauseState::Paused,
+            PauseState::Paused => PauseState::Running,
+        });
+    }
+}
+
+fn pause_on_focus_lost(
+    mut focus_events: EventReader<WindowFocused>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+) {
+    if focus_events.read().any(|event| !event.focused) {
+        next_pause_state.set(PauseState::Paused);
+    }
+}
+
+fn pause_game(
+    mut physics_time: ResMut<Time<Physics>>,
+    mut query_window: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    physics_time.pause();
+    // A drag may have been interrupted while the cursor was hidden.
+    for mut window in query_window.iter_mut() {
+        window.cursor.visible = true;
+    }
+}
+
+fn resume_game(mut physics_time: ResMut<Time<Physics>>) {
+    physics_time.unpause();
+}
+
+fn create_pause_menu(mut commands: Commands, fonts: Res<FontAssets>) {
+    commands
+        .spawn((screen_root(), PauseMenu))
+        .with_children(|parent| {
+            parent.spawn(title(&fonts, "Paused"));
+            spawn_button(parent, &fonts, "Resume", PauseButton::Resume);
+            spawn_button(parent, &fonts, "Restart", PauseButton::Restart);
+            spawn_button(parent, &fonts, "Settings", PauseButton::Settings);
+            spawn_button(parent, &fonts, "Quit to menu", PauseButton::Quit);
+        });
+}
+
+fn pause_menu_action(
+    query_buttons: Query<(&Interaction, &PauseButton), Changed<Interaction>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+    mut next_settings_state: ResMut<NextState<SettingsState>>,
+) {
+    for (interaction, button) in query_buttons.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match button {
+            PauseButton::Resume => next_pause_state.set(PauseState::Running),
+            PauseButton::Restart => next_state.set(GameState::Restarting),
+            PauseButton::Settings => next_settings_state.set(SettingsState::Open),
+            PauseButton::Quit => next_state.set(GameState::MainMenu),
+        }
+    }
+}
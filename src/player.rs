@@ -1,21 +1,37 @@
 use crate::drag::Drag;
-use crate::score::Score;
-use crate::{GameState, Height, MaterialHandles, MeshHandles, HALF_WORLD_SIZE};
+use crate::events::{Climbed, DeathCause, Died, Jumped, RunStarted};
+use crate::powerup::ActivePowerUps;
+use crate::settings::Settings;
+use crate::world::r#box::HeldBox;
+use crate::world::surface::Glue;
+use crate::{
+    GameMode, GameState, Height, MaterialHandles, MeshHandles, PauseState, HALF_WORLD_SIZE,
+};
 use avian2d::prelude::{
-    AngularDamping, Collider, ColliderMassProperties, DistanceJoint, ExternalAngularImpulse,
-    ExternalForce, ExternalImpulse, Friction, Joint, LinearDamping, Restitution, RigidBody,
+    AngularDamping, Collider, ColliderDensity, ColliderMassProperties, DistanceJoint,
+    ExternalAngularImpulse, ExternalForce, ExternalImpulse, Friction, Gravity, GravityScale, Joint,
+    LinearDamping, LinearVelocity, Restitution, RigidBody, Sensor, SpatialQuery,
+    SpatialQueryFilter,
 };
 use bevy::app::{App, Plugin, Update};
+use bevy::ecs::system::SystemParam;
 use bevy::math::{Quat, Vec2};
 use bevy::prelude::{
-    default, in_state, Bundle, ColorMaterial, Commands, Component, Entity, EventReader,
-    FixedUpdate, Handle, IntoSystemConfigs, NextState, OnEnter, OnExit, Or, Query, Res, ResMut,
-    Resource, Time, Timer, Transform, Vec3, With, Without,
+    default, in_state, not, resource_equals, Bundle, Color, ColorMaterial, Commands, Component,
+    Entity, EventReader, EventWriter, FixedUpdate, Gizmos, Handle, IntoSystemConfigs, Local,
+    OnEnter, OnExit, Or, Query, Res, ResMut, Resource, Time, Timer, Transform, Vec3, With, Without,
 };
 use bevy::sprite::{MaterialMesh2dBundle, Mesh2dHandle};
 use bevy::time::TimerMode;
+use bevy_persistent::Persistent;
 use std::time::Duration;
 
+/// Size of the squares the blob is built from.
+pub static BLOB_SIZE: f32 = 2.;
+pub static BLOB_RESTITUTION: f32 = 0.5;
+/// Squares along each side of the blob.
+static BLOB_SQUARES: usize = 9;
+
 #[derive(Component)]
 pub struct Player;
 
@@ -28,10 +44,21 @@ pub struct InnerPlayer;
 #[derive(Component)]
 struct DragIndicator;
 
+/// Highest point the center of the blob has reached this run.
+#[derive(Component)]
+struct HighestPoint(f32);
+
+#[derive(Component)]
+struct Dead;
+
 #[derive(Resource)]
 struct MaxDrag(f32);
 #[derive(Resource)]
-struct JumpTimer(Timer);
+pub struct JumpTimer(pub Timer);
+
+/// Size of the squares the blob should be built from. The blob is rebuilt when it changes.
+#[derive(Resource)]
+pub struct BlobSize(pub f32);
 
 pub struct PlayerPlugin;
 
@@ -42,15 +69,21 @@ impl Plugin for PlayerPlugin {
             TimerMode::Once,
         )))
         .insert_resource(MaxDrag(120.0))
+        .insert_resource(BlobSize(BLOB_SIZE))
         .add_systems(OnEnter(GameState::InGame), create_player)
         .add_systems(OnExit(GameState::InGame), remove_player)
         .add_systems(
             Update,
-            (jump, drag_indicator).run_if(in_state(GameState::InGame)),
+            (jump, drag_indicator, trajectory_preview).run_if(in_state(PauseState::Running)),
         )
         .add_systems(
             FixedUpdate,
-            (player_height, light_up_player).run_if(in_state(GameState::InGame)),
+            (
+                player_height,
+                resize_blob,
+                light_up_player.run_if(not(resource_equals(GameMode::Hardcore))),
+            )
+                .run_if(in_state(PauseState::Running)),
         );
     }
 }
@@ -66,6 +99,8 @@ pub struct PlayerBundle {
     angular_damping: AngularDamping,
     friction: Friction,
     restitution: Restitution,
+    gravity_scale: GravityScale,
+    collider_density: ColliderDensity,
     material_mesh: MaterialMesh2dBundle<ColorMaterial>,
     player: Player,
 }
@@ -86,7 +121,9 @@ impl PlayerBundle {
             linear_damping: LinearDamping(0.1),
             angular_damping: AngularDamping(0.1),
             friction: Friction::new(0.7),
-            restitution: Restitution::new(0.5),
+            restitution: Restitution::new(BLOB_RESTITUTION),
+            gravity_scale: GravityScale(1.),
+            collider_density: ColliderDensity(1.),
             material_mesh: MaterialMesh2dBundle {
                 mesh: mesh_handle,
                 material: material_handle,
@@ -105,24 +142,47 @@ fn create_player(
     mesh_handles: Res<MeshHandles>,
     material_handles: Res<MaterialHandles>,
     mut jump_timer: ResMut<JumpTimer>,
+    mut run_started_event: EventWriter<RunStarted>,
 ) {
     jump_timer.0.reset();
+    run_started_event.send(RunStarted);
+
+    let center = spawn_blob(
+        &mut commands,
+        &mesh_handles,
+        &material_handles,
+        Vec2::new(0., -160.),
+        BLOB_SIZE,
+        Vec2::ZERO,
+    );
+    commands.entity(center).insert(HighestPoint(f32::MIN));
+}
 
-    let num_rows = 9;
+/// Builds the blob as a lattice of squares of `size` held together by joints, with its lower left
+/// corner at `origin`. Returns the center square.
+fn spawn_blob(
+    commands: &mut Commands,
+    mesh_handles: &MeshHandles,
+    material_handles: &MaterialHandles,
+    origin: Vec2,
+    size: f32,
+    velocity: Vec2,
+) -> Entity {
+    let num_rows = BLOB_SQUARES;
     let num_cols = num_rows;
-    let size = 2.;
     let gap: f32 = size / 2.;
     let d_gap = (size * size + gap * gap).sqrt();
     let compliance = 0.00015 / size;
 
+    let mut center_square = Entity::PLACEHOLDER;
     let mut rows: Vec<Vec<Entity>> = Vec::with_capacity(num_rows);
     for r in 0..num_rows {
         let mut row = Vec::with_capacity(num_cols);
         for c in 0..num_cols {
             let edge = r == 0 || r == num_rows - 1 || c == 0 || c == num_cols - 1;
             let center = r == num_rows / 2 && c == num_cols / 2;
-            let x = c as f32 * (size + gap) + size / 2.;
-            let y = r as f32 * (size + gap) + size / 2. - 160.;
+            let x = origin.x + c as f32 * (size + gap) + size / 2.;
+            let y = origin.y + r as f32 * (size + gap) + size / 2.;
 
             let player_bundle = PlayerBundle::new(
                 mesh_handles.rectangle_2.clone(),
@@ -135,12 +195,13 @@ fn create_player(
                 size,
             );
 
-            let mut player = commands.spawn(player_bundle);
+            let mut player = commands.spawn((player_bundle, LinearVelocity(velocity)));
             if !edge {
                 player.insert(InnerPlayer);
             }
             if center {
                 player.insert(CenterPlayer);
+                center_square = player.id();
             }
             row.push(player.id());
         }
@@ -219,12 +280,99 @@ fn create_player(
             }
         }
     }
+
+    center_square
 }
 
-fn remove_player(mut commands: Commands, query_player: Query<Entity, WithPlayerOrDragIndicator>) {
+fn remove_player(
+    mut commands: Commands,
+    query_player: Query<Entity, WithPlayerOrDragIndicator>,
+    mut blob_size: ResMut<BlobSize>,
+    mut power_ups: ResMut<ActivePowerUps>,
+) {
     for entity in query_player.iter() {
         commands.entity(entity).despawn();
     }
+    power_ups.clear();
+    blob_size.0 = BLOB_SIZE;
+}
+
+/// The squares of the blob, the joints holding them together and to whatever they cling to,
+/// and whatever is around them.
+#[derive(SystemParam)]
+struct Blob<'w, 's> {
+    query_squares: Query<'w, 's, Entity, With<Player>>,
+    query_joints: Query<'w, 's, (Entity, &'static DistanceJoint)>,
+    query_glue: Query<'w, 's, Entity, With<Glue>>,
+    held_box: ResMut<'w, HeldBox>,
+    spatial_query: SpatialQuery<'w, 's>,
+    query_sensor: Query<'w, 's, (), With<Sensor>>,
+}
+
+impl Blob<'_, '_> {
+    /// Whether a blob built from squares of this size fits around the center without
+    /// overlapping anything solid.
+    fn has_room(&self, center: Vec2, size: f32) -> bool {
+        let side = BLOB_SQUARES as f32 * size * 1.5 - size / 2.;
+        let filter = SpatialQueryFilter::from_excluded_entities(self.query_squares.iter());
+        self.spatial_query
+            .shape_intersections(&Collider::rectangle(side, side), center, 0., filter)
+            .into_iter()
+            .all(|entity| self.query_sensor.contains(entity))
+    }
+
+    fn despawn(&mut self, commands: &mut Commands) {
+        self.held_box.release(commands);
+        for entity in self.query_glue.iter() {
+            commands.entity(entity).despawn();
+        }
+        for (entity, joint) in self.query_joints.iter() {
+            if self.query_squares.contains(joint.entity1) {
+                commands.entity(entity).despawn();
+            }
+        }
+        for entity in self.query_squares.iter() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// Rebuilds the blob where it is when its size changes, keeping it moving as it was.
+fn resize_blob(
+    mut commands: Commands,
+    mesh_handles: Res<MeshHandles>,
+    material_handles: Res<MaterialHandles>,
+    blob_size: Res<BlobSize>,
+    query_center: Query<(&Transform, &LinearVelocity, &HighestPoint), AlivePlayerCenter>,
+    mut blob: Blob,
+) {
+    let Ok((transform, velocity, highest_point)) = query_center.get_single() else {
+        return;
+    };
+    let size = blob_size.0;
+    if transform.scale.x == size {
+        return;
+    }
+    // Growing back waits until the bigger blob won't end up inside something.
+    let center = transform.translation.truncate();
+    if size > transform.scale.x && !blob.has_room(center, size) {
+        return;
+    }
+
+    blob.despawn(&mut commands);
+
+    let center_offset = (BLOB_SQUARES / 2) as f32 * size * 1.5 + size / 2.;
+    let center = spawn_blob(
+        &mut commands,
+        &mesh_handles,
+        &material_handles,
+        center - Vec2::splat(center_offset),
+        size,
+        velocity.0,
+    );
+    commands
+        .entity(center)
+        .insert(HighestPoint(highest_point.0));
 }
 
 fn jump(
@@ -240,6 +388,7 @@ fn jump(
     mut mouse_drag_event: EventReader<Drag>,
     max_drag: Res<MaxDrag>,
     mut jump_timer: ResMut<JumpTimer>,
+    mut jumped_event: EventWriter<Jumped>,
     time: Res<Time>,
 ) {
     jump_timer.0.tick(time.delta());
@@ -248,15 +397,12 @@ fn jump(
             if drag.done {
                 for (mut impulse, mut angular_impulse, _, mass_props) in query_player.iter_mut() {
                     let drag = (drag.end - drag.start).clamp_length_max(max_drag.0);
-                    let impulse_vec = Vec2 {
-                        x: drag.x.signum() * drag.x.abs().sqrt() * mass_props.mass.0 * -30.,
-                        y: drag.y.signum() * drag.y.abs().sqrt() * mass_props.mass.0 * -60.,
-                    };
-                    impulse.set_impulse(impulse_vec);
+                    impulse.set_impulse(jump_velocity(drag) * mass_props.mass.0);
 
                     angular_impulse.set_impulse(drag.x * 40.);
                 }
                 jump_timer.0.reset();
+                jumped_event.send(Jumped);
             } else {
                 for (_, _, mut force, mass_props) in query_player.iter_mut() {
                     let drag = (drag.end - drag.start).clamp_length_max(max_drag.0);
@@ -324,26 +470,86 @@ fn drag_indicator(
     }
 }
 
-fn player_height(
-    mut next_state: ResMut<NextState<GameState>>,
-    mut height: ResMut<Height>,
-    mut score: ResMut<Score>,
-    query_player: Query<&Transform, With<CenterPlayer>>,
+/// Velocity change a finished drag gives every square of the blob, matching the impulse in `jump`.
+pub fn jump_velocity(drag: Vec2) -> Vec2 {
+    Vec2 {
+        x: drag.x.signum() * drag.x.abs().sqrt() * -30.,
+        y: drag.y.signum() * drag.y.abs().sqrt() * -60.,
+    }
+}
+
+/// What decides whether and how far the blob would fly on a jump.
+#[derive(SystemParam)]
+struct JumpLimits<'w> {
+    max_drag: Res<'w, MaxDrag>,
+    jump_timer: Res<'w, JumpTimer>,
+    gravity: Res<'w, Gravity>,
+}
+
+fn trajectory_preview(
+    mut mouse_drag_event: EventReader<Drag>,
+    mut current_drag: Local<Option<Vec2>>,
+    query_player: Query<(&Transform, &LinearVelocity, &GravityScale), With<CenterPlayer>>,
+    settings: Res<Persistent<Settings>>,
+    limits: JumpLimits,
+    mut gizmos: Gizmos,
 ) {
-    let player_pos = query_player.single().translation;
+    for drag in mouse_drag_event.read() {
+        *current_drag = (!drag.done).then_some(drag.end - drag.start);
+    }
 
-    if player_pos.y > height.0 + 50. {
-        height.0 = player_pos.y;
+    let Some(drag) = *current_drag else {
+        return;
+    };
+    let Ok((transform, velocity, gravity_scale)) = query_player.get_single() else {
+        return;
+    };
+    if !settings.trajectory_preview || !limits.jump_timer.0.finished() {
+        return;
     }
 
-    if player_pos.y as u32 > score.0 {
-        score.0 = player_pos.y as u32;
+    let start = transform.translation.truncate();
+    let velocity = velocity.0 + jump_velocity(drag.clamp_length_max(limits.max_drag.0));
+    let gravity = limits.gravity.0 * gravity_scale.0;
+    for step in 1..=20 {
+        let t = step as f32 * 0.05;
+        let position = start + velocity * t + gravity * t * t / 2.;
+        gizmos.circle_2d(position, 1.5, Color::srgba(1., 1., 1., 0.6));
     }
+}
+
+type AlivePlayerCenter = (With<CenterPlayer>, Without<Dead>);
+
+fn player_height(
+    mut commands: Commands,
+    height: Res<Height>,
+    mut climbed_event: EventWriter<Climbed>,
+    mut died_event: EventWriter<Died>,
+    mut query_player: Query<(Entity, &Transform, &mut HighestPoint), AlivePlayerCenter>,
+) {
+    let Ok((entity, transform, mut highest_point)) = query_player.get_single_mut() else {
+        return;
+    };
+    let player_pos = transform.translation;
+
+    if player_pos.y > highest_point.0 {
+        highest_point.0 = player_pos.y;
+        climbed_event.send(Climbed {
+            height: player_pos.y,
+        });
+    }
+
+    let cause = if player_pos.y < height.0 - HALF_WORLD_SIZE {
+        Some(DeathCause::FellBelowLine)
+    } else if !((-HALF_WORLD_SIZE - 50.)..(HALF_WORLD_SIZE + 50.)).contains(&player_pos.x) {
+        Some(DeathCause::OutOfBounds)
+    } else {
+        None
+    };
 
-    if player_pos.y < height.0 - HALF_WORLD_SIZE
-        || !((-HALF_WORLD_SIZE - 50.)..(HALF_WORLD_SIZE + 50.)).contains(&player_pos.x)
-    {
-        next_state.set(GameState::GameOver);
+    if let Some(cause) = cause {
+        commands.entity(entity).insert(Dead);
+        died_event.send(Died { cause });
     }
 }
 
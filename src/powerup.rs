@@ -0,0 +1,333 @@
+use crate::player::{BlobSize, JumpTimer, Player, BLOB_RESTITUTION, BLOB_SIZE};
+use crate::ui::{despawn_screen, text, TEXT_COLOR};
+use crate::world::WorldEntity;
+use crate::{FontAssets, GameState, ImageAssets, PauseState};
+use avian2d::collision::Collider;
+use avian2d::prelude::{
+    ColliderDensity, CollisionStarted, Collisions, GravityScale, Restitution, RigidBody, Sensor,
+};
+use bevy::app::App;
+use bevy::hierarchy::{BuildChildren, DespawnRecursiveExt};
+use bevy::math::Vec2;
+use bevy::prelude::{
+    default, in_state, AlignItems, Commands, Component, DetectChangesMut, Entity, EventReader,
+    FixedUpdate, FlexDirection, Handle, Image, ImageBundle, IntoSystemConfigs, NodeBundle, OnEnter,
+    OnExit, Plugin, PositionType, Query, Res, ResMut, Resource, Sprite, SpriteBundle, Style, Text,
+    Time, Timer, TimerMode, Transform, UiImage, UiRect, Update, Val, With,
+};
+use rand::Rng;
+
+static ALL_POWER_UPS: &[PowerUp] = &[
+    PowerUp::DoubleJump,
+    PowerUp::Feather,
+    PowerUp::Heavy,
+    PowerUp::Shrink,
+    PowerUp::Sticky,
+];
+static PICKUP_SIZE: f32 = 14.;
+static FEATHER_GRAVITY: f32 = 0.5;
+static HEAVY_DENSITY: f32 = 3.;
+static HEAVY_RESTITUTION: f32 = 0.9;
+static SHRUNK_SIZE: f32 = 1.2;
+
+pub struct PowerUpsPlugin;
+
+impl Plugin for PowerUpsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ActivePowerUps::default())
+            .insert_resource(AirJump { used: false })
+            .add_systems(OnEnter(GameState::InGame), create_power_up_hud)
+            .add_systems(OnExit(GameState::InGame), despawn_screen::<PowerUpHud>)
+            .add_systems(
+                Update,
+                update_power_up_hud.run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    collect_power_ups,
+                    tick_power_ups,
+                    apply_power_ups,
+                    double_jump,
+                )
+                    .chain()
+                    .run_if(in_state(PauseState::Running)),
+            );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PowerUp {
+    /// Lets the blob jump once more while in the air.
+    DoubleJump,
+    /// Halves the gravity pulling on the blob.
+    Feather,
+    /// Makes the blob heavier and bouncier.
+    Heavy,
+    /// Rebuilds the blob smaller.
+    Shrink,
+    /// The blob clings to whatever it touches.
+    Sticky,
+}
+
+impl PowerUp {
+    fn duration(&self) -> f32 {
+        match self {
+            PowerUp::DoubleJump => 15.,
+            PowerUp::Feather | PowerUp::Heavy | PowerUp::Sticky => 10.,
+            PowerUp::Shrink => 8.,
+        }
+    }
+
+    fn icon(&self, images: &ImageAssets) -> Handle<Image> {
+        let index = ALL_POWER_UPS
+            .iter()
+            .position(|power_up| power_up == self)
+            .unwrap_or(0);
+        images.power_ups[index].clone()
+    }
+}
+
+/// The power-ups the blob has picked up this run, with the time each has left.
+#[derive(Resource, Default)]
+pub struct ActivePowerUps(Vec<(PowerUp, Timer)>);
+
+impl ActivePowerUps {
+    pub fn has(&self, power_up: PowerUp) -> bool {
+        self.0.iter().any(|(active, _)| *active == power_up)
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    /// Starts the power-up, or starts it over if it is already going.
+    fn activate(&mut self, power_up: PowerUp) {
+        let timer = Timer::from_seconds(power_up.duration(), TimerMode::Once);
+        match self.0.iter_mut().find(|(active, _)| *active == power_up) {
+            Some((_, active_timer)) => *active_timer = timer,
+            None => self.0.push((power_up, timer)),
+        }
+    }
+}
+
+/// Whether the blob has used up its extra jump since it last touched something.
+#[derive(Resource)]
+struct AirJump {
+    used: bool,
+}
+
+#[derive(Component)]
+struct PowerUpPickup(PowerUp);
+
+#[derive(Component)]
+struct PowerUpHud;
+
+#[derive(Component)]
+struct PowerUpHudEntry(PowerUp);
+
+#[derive(Component)]
+struct PowerUpHudTimer(PowerUp);
+
+/// Which power-up, if any, to put above a generated platform.
+pub fn roll_power_up(rng: &mut impl Rng) -> Option<PowerUp> {
+    rng.gen_bool(0.05)
+        .then(|| ALL_POWER_UPS[rng.gen_range(0..ALL_POWER_UPS.len())])
+}
+
+pub fn spawn_power_up(commands: &mut Commands, images: &ImageAssets, pos: Vec2, power_up: PowerUp) {
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                custom_size: Some(Vec2::splat(PICKUP_SIZE)),
+                ..default()
+            },
+            transform: Transform::from_translation(pos.extend(5.)),
+            texture: power_up.icon(images),
+            ..default()
+        },
+        RigidBody::Static,
+        Collider::circle(PICKUP_SIZE / 2.),
+        Sensor,
+        PowerUpPickup(power_up),
+        WorldEntity,
+    ));
+}
+
+fn collect_power_ups(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionStarted>,
+    query_player: Query<(), With<Player>>,
+    query_pickup: Query<&PowerUpPickup>,
+    mut power_ups: ResMut<ActivePowerUps>,
+) {
+    let mut collected: Vec<Entity> = Vec::new();
+    for CollisionStarted(entity1, entity2) in collision_events.read() {
+        let pickup = if query_player.contains(*entity1) {
+            *entity2
+        } else if query_player.contains(*entity2) {
+            *entity1
+        } else {
+            continue;
+        };
+        let Ok(PowerUpPickup(power_up)) = query_pickup.get(pickup) else {
+            continue;
+        };
+        if collected.contains(&pickup) {
+            continue;
+        }
+
+        collected.push(pickup);
+        power_ups.activate(*power_up);
+        commands.entity(pickup).despawn();
+    }
+}
+
+fn tick_power_ups(time: Res<Time>, mut power_ups: ResMut<ActivePowerUps>) {
+    if power_ups.0.is_empty() {
+        return;
+    }
+    power_ups
+        .0
+        .retain_mut(|(_, timer)| !timer.tick(time.delta()).finished());
+}
+
+/// Sets up the blob's squares for the power-ups that are going, and back once they run out.
+fn apply_power_ups(
+    power_ups: Res<ActivePowerUps>,
+    mut blob_size: ResMut<BlobSize>,
+    mut query_player: Query<
+        (&mut GravityScale, &mut ColliderDensity, &mut Restitution),
+        With<Player>,
+    >,
+) {
+    let feather = power_ups.has(PowerUp::Feather);
+    let heavy = power_ups.has(PowerUp::Heavy);
+
+    for (mut gravity_scale, mut density, mut restitution) in query_player.iter_mut() {
+        gravity_scale.set_if_neq(GravityScale(if feather { FEATHER_GRAVITY } else { 1. }));
+        density.set_if_neq(ColliderDensity(if heavy { HEAVY_DENSITY } else { 1. }));
+        restitution.set_if_neq(Restitution::new(if heavy {
+            HEAVY_RESTITUTION
+        } else {
+            BLOB_RESTITUTION
+        }));
+    }
+
+    blob_size.0 = if power_ups.has(PowerUp::Shrink) {
+        SHRUNK_SIZE
+    } else {
+        BLOB_SIZE
+    };
+}
+
+/// Lets the blob jump again right away, once per time in the air.
+fn double_jump(
+    power_ups: Res<ActivePowerUps>,
+    collisions: Res<Collisions>,
+    query_player: Query<(), With<Player>>,
+    query_sensor: Query<(), With<Sensor>>,
+    mut jump_timer: ResMut<JumpTimer>,
+    mut air_jump: ResMut<AirJump>,
+) {
+    if !power_ups.has(PowerUp::DoubleJump) {
+        return;
+    }
+
+    let solid = |entity: Entity| !query_player.contains(entity) && !query_sensor.contains(entity);
+    let touching = collisions.iter().any(|contacts| {
+        contacts.during_current_frame
+            && ((query_player.contains(contacts.entity1) && solid(contacts.entity2))
+                || (query_player.contains(contacts.entity2) && solid(contacts.entity1)))
+    });
+
+    if touching {
+        air_jump.used = false;
+    } else if !air_jump.used && !jump_timer.0.finished() {
+        let duration = jump_timer.0.duration();
+        jump_timer.0.set_elapsed(duration);
+        air_jump.used = true;
+    }
+}
+
+fn create_power_up_hud(mut commands: Commands, mut air_jump: ResMut<AirJump>) {
+    air_jump.used = false;
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.),
+                right: Val::Px(10.),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::End,
+                ..default()
+            },
+            ..default()
+        },
+        PowerUpHud,
+    ));
+}
+
+/// Shows an icon and the seconds left for each power-up that is going.
+fn update_power_up_hud(
+    mut commands: Commands,
+    images: Res<ImageAssets>,
+    fonts: Res<FontAssets>,
+    power_ups: Res<ActivePowerUps>,
+    query_hud: Query<Entity, With<PowerUpHud>>,
+    query_entries: Query<(Entity, &PowerUpHudEntry)>,
+    mut query_timers: Query<(&mut Text, &PowerUpHudTimer)>,
+) {
+    let Ok(hud) = query_hud.get_single() else {
+        return;
+    };
+
+    for (entity, PowerUpHudEntry(power_up)) in query_entries.iter() {
+        if !power_ups.has(*power_up) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+    for (power_up, _) in power_ups.0.iter() {
+        if query_entries.iter().any(|(_, entry)| entry.0 == *power_up) {
+            continue;
+        }
+        commands.entity(hud).with_children(|parent| {
+            parent
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            align_items: AlignItems::Center,
+                            margin: UiRect::bottom(Val::Px(4.)),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    PowerUpHudEntry(*power_up),
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        text(&fonts, "", 20., TEXT_COLOR).with_style(Style {
+                            margin: UiRect::right(Val::Px(6.)),
+                            ..default()
+                        }),
+                        PowerUpHudTimer(*power_up),
+                    ));
+                    parent.spawn(ImageBundle {
+                        style: Style {
+                            width: Val::Px(24.),
+                            height: Val::Px(24.),
+                            ..default()
+                        },
+                        image: UiImage::new(power_up.icon(&images)),
+                        ..default()
+                    });
+                });
+        });
+    }
+
+    for (mut text, PowerUpHudTimer(power_up)) in query_timers.iter_mut() {
+        if let Some((_, timer)) = power_ups.0.iter().find(|(active, _)| active == power_up) {
+            text.sections[0].value = format!("{:.0}", timer.remaining_secs().ceil());
+        }
+    }
+}
@@ -1,37 +1,63 @@
-use crate::{get_state_directory, FontAssets, GameState, Height};
+use crate::events::{Climbed, Jumped, Landed, NewHighScore, PlatformReached};
+use crate::leaderboard::{record_run, LatestRank, Leaderboard};
+use crate::player::CenterPlayer;
+use crate::ui::{despawn_screen, format_duration, spawn_button};
+use crate::{FontAssets, GameMode, GameState, Height, PauseState, Run, HALF_WORLD_SIZE};
+use avian2d::prelude::AngularVelocity;
 use bevy::app::App;
+use bevy::ecs::system::SystemParam;
+use bevy::hierarchy::BuildChildren;
+use bevy::input::ButtonInput;
 use bevy::math::Vec2;
 use bevy::prelude::{
-    default, in_state, Camera, Color, Commands, Component, Entity, FixedUpdate, GlobalTransform,
-    IntoSystemConfigs, JustifyText, Local, OnEnter, OnExit, Plugin, Query, Res, ResMut, Resource,
-    Text, Text2dBundle, TextStyle, Transform, Update, Vec3, With,
+    default, in_state, not, Camera, Changed, Color, Commands, Component, Entity, EventReader,
+    EventWriter, FixedUpdate, GlobalTransform, Interaction, IntoSystemConfigs, JustifyContent,
+    JustifyText, KeyCode, Local, NextState, NodeBundle, OnEnter, OnExit, Plugin, PositionType,
+    Query, Res, ResMut, Resource, Style, Text, Text2dBundle, TextStyle, Time, Transform, Update,
+    Val, Vec3, With,
 };
 use bevy::sprite::Anchor;
-use bevy_persistent::{Persistent, StorageFormat};
-use serde::{Deserialize, Serialize};
+use bevy_persistent::Persistent;
+use std::f32::consts::TAU;
+
+static PLATFORM_POINTS: u32 = 10;
+static MAX_COMBO_MULTIPLIER: u32 = 5;
+static FLIP_POINTS: u32 = 50;
+static RISK_POINTS: u32 = 30;
+static RISK_DISTANCE: f32 = 60.;
 
 pub struct ScorePlugin;
 
 impl Plugin for ScorePlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(Score(0))
+            .insert_resource(ScoreBreakdown::default())
+            .insert_resource(LandingTracker::default())
             .add_systems(OnEnter(GameState::InGame), create_score_text)
             .add_systems(OnExit(GameState::InGame), remove_score_text)
             .add_systems(Update, scroll_score.run_if(in_state(GameState::InGame)))
             .add_systems(
                 FixedUpdate,
-                update_score.run_if(in_state(GameState::InGame)),
+                (
+                    score_height.run_if(scored_by_height),
+                    score_platforms.run_if(not(scored_by_height)),
+                    check_high_score,
+                    update_score,
+                )
+                    .chain()
+                    .run_if(in_state(PauseState::Running)),
             )
-            .add_systems(OnEnter(GameState::GameOver), create_game_over)
-            .add_systems(OnExit(GameState::GameOver), remove_score_text)
-            .insert_resource(
-                Persistent::<HighScore>::builder()
-                    .name("high_score")
-                    .format(StorageFormat::Json)
-                    .path(get_state_directory().join("high_score.json"))
-                    .default(HighScore::default())
-                    .build()
-                    .expect("Failed to initialize high score"),
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                create_game_over.after(record_run),
+            )
+            .add_systems(
+                Update,
+                game_over_action.run_if(in_state(GameState::GameOver)),
+            )
+            .add_systems(
+                OnExit(GameState::GameOver),
+                (remove_score_text, despawn_screen::<GameOverMenu>),
             );
     }
 }
@@ -39,19 +65,64 @@ impl Plugin for ScorePlugin {
 #[derive(Resource)]
 pub struct Score(pub u32);
 
-#[derive(Default, Resource, Serialize, Deserialize)]
-struct HighScore(u32);
+/// How the score of the current run was earned in the platform based modes.
+#[derive(Resource, Default)]
+pub struct ScoreBreakdown {
+    pub platforms: u32,
+    pub combo: u32,
+    pub flips: u32,
+    pub risk: u32,
+}
+
+impl ScoreBreakdown {
+    pub fn total(&self) -> u32 {
+        self.platforms + self.combo + self.flips + self.risk
+    }
+}
+
+/// What the blob has done since it last landed, used to judge the next landing.
+#[derive(Resource, Default)]
+struct LandingTracker {
+    jumps: u32,
+    rotation: f32,
+    combo: u32,
+}
 
 #[derive(Component)]
 pub struct ScoreText;
 
+#[derive(Component)]
+struct GameOverMenu;
+
+#[derive(Component)]
+enum GameOverButton {
+    Retry,
+    Menu,
+}
+
+/// Whether the current run has already beaten the leaderboard's best score.
+#[derive(Resource)]
+struct BeatHighScore(bool);
+
+fn scored_by_height(mode: Res<GameMode>) -> bool {
+    mode.scored_by_height()
+}
+
 fn create_score_text(
     mut commands: Commands,
     fonts: Res<FontAssets>,
     mut score: ResMut<Score>,
+    leaderboard: Res<Persistent<Leaderboard>>,
+    mode: Res<GameMode>,
     query_camera: Query<(&Camera, &GlobalTransform)>,
 ) {
     score.0 = 0;
+    commands.insert_resource(ScoreBreakdown::default());
+    commands.insert_resource(LandingTracker::default());
+    // Runs ranked by time only know whether they beat the best once they are finished.
+    commands.insert_resource(BeatHighScore(
+        mode.ranked_by_time() || leaderboard.best(*mode).is_none(),
+    ));
     let (camera, camera_transform) = query_camera.single();
     let score_pos = camera
         .viewport_to_world_2d(camera_transform, Vec2::new(10., 10.))
@@ -96,76 +167,239 @@ fn scroll_score(
     }
 }
 
+fn score_height(mut climbed_events: EventReader<Climbed>, mut score: ResMut<Score>) {
+    for climbed in climbed_events.read() {
+        if climbed.height as u32 > score.0 {
+            score.0 = climbed.height as u32;
+        }
+    }
+}
+
+/// What the blob did since the last tick: jumps, landings and platforms reached.
+#[derive(SystemParam)]
+struct JumpEvents<'w, 's> {
+    jumped: EventReader<'w, 's, Jumped>,
+    landed: EventReader<'w, 's, Landed>,
+    platform_reached: EventReader<'w, 's, PlatformReached>,
+}
+
+fn score_platforms(
+    time: Res<Time>,
+    height: Res<Height>,
+    mut events: JumpEvents,
+    query_player: Query<&AngularVelocity, With<CenterPlayer>>,
+    mut tracker: ResMut<LandingTracker>,
+    mut breakdown: ResMut<ScoreBreakdown>,
+    mut score: ResMut<Score>,
+) {
+    tracker.jumps += events.jumped.read().count() as u32;
+    if let Ok(angular_velocity) = query_player.get_single() {
+        tracker.rotation += angular_velocity.0 * time.delta_seconds();
+    }
+
+    let reached = events.platform_reached.read().collect::<Vec<_>>();
+    for landed in events.landed.read() {
+        if let Some(reached) = reached.iter().find(|r| r.platform == landed.platform) {
+            if tracker.jumps == 1 {
+                tracker.combo += 1;
+            } else {
+                tracker.combo = 0;
+            }
+            let multiplier = tracker.combo.clamp(1, MAX_COMBO_MULTIPLIER);
+            breakdown.platforms += PLATFORM_POINTS;
+            breakdown.combo += PLATFORM_POINTS * (multiplier - 1);
+            breakdown.flips += FLIP_POINTS * (tracker.rotation.abs() / TAU) as u32;
+            if reached.height - (height.0 - HALF_WORLD_SIZE) < RISK_DISTANCE {
+                breakdown.risk += RISK_POINTS;
+            }
+        } else {
+            tracker.combo = 0;
+        }
+        tracker.jumps = 0;
+        tracker.rotation = 0.;
+    }
+
+    score.0 = breakdown.total();
+}
+
+fn check_high_score(
+    score: Res<Score>,
+    mode: Res<GameMode>,
+    leaderboard: Res<Persistent<Leaderboard>>,
+    mut beat_high_score: ResMut<BeatHighScore>,
+    mut new_high_score_event: EventWriter<NewHighScore>,
+) {
+    if !beat_high_score.0 && score.0 > leaderboard.high_score(*mode) {
+        beat_high_score.0 = true;
+        new_high_score_event.send(NewHighScore);
+    }
+}
+
 fn update_score(
     mut old_score: Local<u32>,
     score: Res<Score>,
-    mut high_score: ResMut<Persistent<HighScore>>,
+    mut new_high_score_events: EventReader<NewHighScore>,
     mut score_query: Query<&mut Text, With<ScoreText>>,
 ) {
-    if *old_score != score.0 {
+    let new_high_score = new_high_score_events.read().count() > 0;
+    if *old_score != score.0 || new_high_score {
         *old_score = score.0;
         for mut text in score_query.iter_mut() {
             text.sections[0].value = format!("{}", score.0);
-        }
-        if score.0 > high_score.0 {
-            high_score.0 = score.0;
+            if new_high_score {
+                text.sections[0].style.color = Color::srgb(3., 1.5, 0.);
+            }
         }
     }
 }
 
+/// Where the run ended up among the others of its mode.
+#[derive(SystemParam)]
+struct Standings<'w> {
+    mode: Res<'w, GameMode>,
+    leaderboard: Res<'w, Persistent<Leaderboard>>,
+    latest_rank: Res<'w, LatestRank>,
+}
+
 fn create_game_over(
     mut commands: Commands,
     fonts: Res<FontAssets>,
     score: Res<Score>,
-    high_score: Res<Persistent<HighScore>>,
+    breakdown: Res<ScoreBreakdown>,
+    standings: Standings,
     height: Res<Height>,
+    run: Res<Run>,
 ) {
-    high_score
-        .persist()
-        .unwrap_or_else(|e| println!("Failed to persist high score: {}", e));
+    let Standings {
+        mode,
+        leaderboard,
+        latest_rank,
+    } = standings;
+    let heading = if mode.is_finished(&run) {
+        "Finished"
+    } else {
+        "Game Over"
+    };
+    let result = if mode.ranked_by_time() {
+        format!("Time {}", format_duration(run.duration))
+    } else {
+        format!("Score {}", score.0)
+    };
+    let (summary, neighbours) = match latest_rank.0 {
+        Some((_, rank)) => {
+            let neighbours = leaderboard
+                .entries(*mode)
+                .enumerate()
+                .skip(rank.saturating_sub(2))
+                .take(5)
+                .map(|(r, entry)| {
+                    let marker = if r == rank { ">" } else { " " };
+                    format!("{}{}", marker, entry.summary(r))
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            (
+                format!("{}\n{}\nRank {}", heading, result, rank + 1),
+                neighbours,
+            )
+        }
+        None => {
+            let best = if mode.ranked_by_time() {
+                leaderboard
+                    .best(*mode)
+                    .map(|e| format!("Best time {}", format_duration(e.duration)))
+                    .unwrap_or_default()
+            } else {
+                format!("High score {}", leaderboard.high_score(*mode))
+            };
+            (format!("{}\n{}\n{}", heading, result, best), String::new())
+        }
+    };
+    let details = if mode.scored_by_height() {
+        neighbours
+    } else {
+        format!(
+            "Platforms {}  Combo {}  Flips {}  Risk {}\n\n{}",
+            breakdown.platforms, breakdown.combo, breakdown.flips, breakdown.risk, neighbours
+        )
+    };
 
     for (color, delta) in [
         (Color::srgb(1.0, 1.0, 0.0), Vec3::new(0., 0., 101.)),
         (Color::srgb(0.0, 0.0, 0.0), Vec3::new(2., -2., 100.)),
     ] {
-        commands.spawn((
-            Text2dBundle {
-                text: Text::from_section(
-                    format!("Game Over\nScore {}\nHigh score {}", score.0, high_score.0),
-                    TextStyle {
-                        font: fonts.segmental.clone(),
-                        font_size: 40.0,
-                        color,
-                    },
-                )
-                .with_justify(JustifyText::Center),
-                text_anchor: Anchor::Center,
-                transform: Transform::from_translation(
-                    Vec3::new(0., height.0 + 50., 0.) + delta,
-                ),
+        let translation = |offset: f32| Vec3::new(0., height.0 + offset, 0.) + delta;
+        for (text, font_size, offset) in
+            [(summary.as_str(), 40.0, 100.), (details.as_str(), 16.0, 0.)]
+        {
+            commands.spawn((
+                game_over_text(&fonts, text, font_size, color, translation(offset)),
+                ScoreText,
+            ));
+        }
+    }
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    bottom: Val::Px(40.),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
                 ..default()
             },
-            ScoreText,
-        ));
-
-        commands.spawn((
-            Text2dBundle {
-                text: Text::from_section(
-                    "Press any key to play again",
-                    TextStyle {
-                        font: fonts.segmental.clone(),
-                        font_size: 30.0,
-                        color,
-                    },
-                )
-                .with_justify(JustifyText::Center),
-                text_anchor: Anchor::Center,
-                transform: Transform::from_translation(
-                    Vec3::new(0., height.0 - 70., 0.) + delta,
-                ),
-                ..default()
+            GameOverMenu,
+        ))
+        .with_children(|parent| {
+            spawn_button(parent, &fonts, "Retry", GameOverButton::Retry);
+            spawn_button(parent, &fonts, "Menu", GameOverButton::Menu);
+        });
+}
+
+/// Enter or Space retries and Escape goes back to the menu, besides the buttons.
+fn game_over_action(
+    keys: Res<ButtonInput<KeyCode>>,
+    query_buttons: Query<(&Interaction, &GameOverButton), Changed<Interaction>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keys.any_just_pressed([KeyCode::Enter, KeyCode::Space]) {
+        next_state.set(GameState::Restarting);
+    } else if keys.just_pressed(KeyCode::Escape) {
+        next_state.set(GameState::MainMenu);
+    }
+
+    for (interaction, button) in query_buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            next_state.set(match button {
+                GameOverButton::Retry => GameState::Restarting,
+                GameOverButton::Menu => GameState::MainMenu,
+            });
+        }
+    }
+}
+
+fn game_over_text(
+    fonts: &FontAssets,
+    text: &str,
+    font_size: f32,
+    color: Color,
+    translation: Vec3,
+) -> Text2dBundle {
+    Text2dBundle {
+        text: Text::from_section(
+            text,
+            TextStyle {
+                font: fonts.segmental.clone(),
+                font_size,
+                color,
             },
-            ScoreText,
-        ));
+        )
+        .with_justify(JustifyText::Center),
+        text_anchor: Anchor::Center,
+        transform: Transform::from_translation(translation),
+        ..default()
     }
 }
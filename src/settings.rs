@@ -0,0 +1,331 @@
+use crate::camera::BLOOM;
+use crate::ui::{despawn_screen, screen_root, spawn_button, text, title, TITLE_COLOR};
+use crate::{get_state_directory, FontAssets};
+use bevy::app::App;
+use bevy::audio::GlobalVolume;
+use bevy::core_pipeline::bloom::BloomSettings;
+use bevy::core_pipeline::tonemapping::Tonemapping;
+use bevy::hierarchy::{BuildChildren, ChildBuilder, Children};
+use bevy::input::ButtonInput;
+use bevy::prelude::{
+    default, in_state, resource_changed, AlignItems, AppExtStates, Camera, Changed, Commands,
+    Component, Entity, FlexDirection, Interaction, IntoSystemConfigs, KeyCode, NextState,
+    NodeBundle, OnEnter, OnExit, Plugin, Query, Res, ResMut, Resource, States, Style, Text, UiRect,
+    Update, Val, Window, With, ZIndex,
+};
+use bevy::ui::{FocusPolicy, UiScale};
+use bevy::window::{PresentMode, PrimaryWindow, WindowMode};
+use bevy_persistent::{Persistent, StorageFormat};
+use bevy_persistent_windows::prelude::WindowState;
+use serde::{Deserialize, Serialize};
+
+static SENSITIVITIES: &[f32] = &[0.5, 0.75, 1., 1.25, 1.5, 2.];
+static VOLUMES: &[f32] = &[0., 0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9, 1.];
+static UI_SCALES: &[f32] = &[0.75, 1., 1.25, 1.5];
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<SettingsState>()
+            .add_systems(OnEnter(SettingsState::Open), create_settings_screen)
+            .add_systems(
+                OnExit(SettingsState::Open),
+                despawn_screen::<SettingsScreen>,
+            )
+            .add_systems(
+                Update,
+                settings_action.run_if(in_state(SettingsState::Open)),
+            )
+            .add_systems(
+                Update,
+                apply_settings.run_if(resource_changed::<Persistent<Settings>>),
+            )
+            .insert_resource(
+                Persistent::<Settings>::builder()
+                    .name("settings")
+                    .format(StorageFormat::Json)
+                    .path(get_state_directory().join("settings.json"))
+                    .default(Settings::default())
+                    .revert_to_default_on_deserialization_errors(true)
+                    .build()
+                    .expect("Failed to initialize settings"),
+            );
+    }
+}
+
+/// Whether the settings screen is shown on top of the menu it was opened from.
+#[derive(States, Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum SettingsState {
+    #[default]
+    Closed,
+    Open,
+}
+
+#[derive(Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub fullscreen: bool,
+    pub vsync: bool,
+    pub bloom: bool,
+    pub tonemapping: TonemappingChoice,
+    /// Show where the blob would fly while aiming a jump.
+    pub trajectory_preview: bool,
+    /// How far the aim moves per mouse movement.
+    pub sensitivity: f32,
+    pub volume: f32,
+    pub ui_scale: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            fullscreen: false,
+            vsync: true,
+            bloom: true,
+            tonemapping: TonemappingChoice::default(),
+            trajectory_preview: false,
+            sensitivity: 1.,
+            volume: 1.,
+            ui_scale: 1.,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug, Serialize, Deserialize)]
+pub enum TonemappingChoice {
+    #[default]
+    TonyMcMapface,
+    AcesFitted,
+    Reinhard,
+    None,
+}
+
+impl TonemappingChoice {
+    fn name(&self) -> &'static str {
+        match self {
+            TonemappingChoice::TonyMcMapface => "TonyMcMapface",
+            TonemappingChoice::AcesFitted => "ACES",
+            TonemappingChoice::Reinhard => "Reinhard",
+            TonemappingChoice::None => "None",
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            TonemappingChoice::TonyMcMapface => TonemappingChoice::AcesFitted,
+            TonemappingChoice::AcesFitted => TonemappingChoice::Reinhard,
+            TonemappingChoice::Reinhard => TonemappingChoice::None,
+            TonemappingChoice::None => TonemappingChoice::TonyMcMapface,
+        }
+    }
+
+    fn tonemapping(self) -> Tonemapping {
+        match self {
+            TonemappingChoice::TonyMcMapface => Tonemapping::TonyMcMapface,
+            TonemappingChoice::AcesFitted => Tonemapping::AcesFitted,
+            TonemappingChoice::Reinhard => Tonemapping::Reinhard,
+            TonemappingChoice::None => Tonemapping::None,
+        }
+    }
+}
+
+#[derive(Component)]
+struct SettingsScreen;
+
+#[derive(Component, Clone, Copy)]
+enum SettingsButton {
+    Fullscreen,
+    Vsync,
+    Bloom,
+    Tonemapping,
+    TrajectoryPreview,
+    Sensitivity,
+    Volume,
+    UiScale,
+    Back,
+}
+
+impl SettingsButton {
+    fn label(self, settings: &Settings) -> String {
+        let on_off = |on: bool| if on { "On" } else { "Off" };
+        match self {
+            SettingsButton::Fullscreen => format!("Fullscreen: {}", on_off(settings.fullscreen)),
+            SettingsButton::Vsync => format!("VSync: {}", on_off(settings.vsync)),
+            SettingsButton::Bloom => format!("Bloom: {}", on_off(settings.bloom)),
+            SettingsButton::Tonemapping => {
+                format!("Tonemapping: {}", settings.tonemapping.name())
+            }
+            SettingsButton::TrajectoryPreview => {
+                format!("Trajectory: {}", on_off(settings.trajectory_preview))
+            }
+            SettingsButton::Sensitivity => format!("Sensitivity: {:.2}", settings.sensitivity),
+            SettingsButton::Volume => format!("Volume: {:.0}%", settings.volume * 100.),
+            SettingsButton::UiScale => format!("UI scale: {:.0}%", settings.ui_scale * 100.),
+            SettingsButton::Back => "Back".to_owned(),
+        }
+    }
+
+    fn change(self, settings: &mut Settings) {
+        match self {
+            SettingsButton::Fullscreen => settings.fullscreen = !settings.fullscreen,
+            SettingsButton::Vsync => settings.vsync = !settings.vsync,
+            SettingsButton::Bloom => settings.bloom = !settings.bloom,
+            SettingsButton::Tonemapping => settings.tonemapping = settings.tonemapping.next(),
+            SettingsButton::TrajectoryPreview => {
+                settings.trajectory_preview = !settings.trajectory_preview
+            }
+            SettingsButton::Sensitivity => {
+                settings.sensitivity = cycle(SENSITIVITIES, settings.sensitivity)
+            }
+            SettingsButton::Volume => settings.volume = cycle(VOLUMES, settings.volume),
+            SettingsButton::UiScale => settings.ui_scale = cycle(UI_SCALES, settings.ui_scale),
+            SettingsButton::Back => {}
+        }
+    }
+}
+
+/// The next of `values` after `current`, wrapping around to the first.
+fn cycle(values: &[f32], current: f32) -> f32 {
+    values
+        .iter()
+        .copied()
+        .find(|value| *value > current + 0.001)
+        .unwrap_or(values[0])
+}
+
+fn create_settings_screen(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    settings: Res<Persistent<Settings>>,
+) {
+    let column = || NodeBundle {
+        style: Style {
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            margin: UiRect::horizontal(Val::Px(20.)),
+            ..default()
+        },
+        ..default()
+    };
+    let section = |parent: &mut ChildBuilder, heading: &str, buttons: &[SettingsButton]| {
+        parent.spawn(text(&fonts, heading, 20., TITLE_COLOR).with_style(Style {
+            margin: UiRect::vertical(Val::Px(10.)),
+            ..default()
+        }));
+        for button in buttons {
+            spawn_button(parent, &fonts, button.label(&settings), *button);
+        }
+    };
+
+    let mut root = screen_root();
+    // Drawn over, and blocking clicks to, the menu it was opened from.
+    root.z_index = ZIndex::Global(10);
+    root.focus_policy = FocusPolicy::Block;
+
+    commands
+        .spawn((root, SettingsScreen))
+        .with_children(|parent| {
+            parent.spawn(title(&fonts, "Settings"));
+            parent.spawn(NodeBundle::default()).with_children(|parent| {
+                parent.spawn(column()).with_children(|parent| {
+                    section(
+                        parent,
+                        "Graphics",
+                        &[
+                            SettingsButton::Fullscreen,
+                            SettingsButton::Vsync,
+                            SettingsButton::Bloom,
+                            SettingsButton::Tonemapping,
+                        ],
+                    );
+                });
+                parent.spawn(column()).with_children(|parent| {
+                    section(
+                        parent,
+                        "Gameplay",
+                        &[
+                            SettingsButton::TrajectoryPreview,
+                            SettingsButton::Sensitivity,
+                        ],
+                    );
+                    section(parent, "Audio", &[SettingsButton::Volume]);
+                    section(parent, "Accessibility", &[SettingsButton::UiScale]);
+                });
+            });
+            spawn_button(parent, &fonts, "Back", SettingsButton::Back);
+        });
+}
+
+fn settings_action(
+    keys: Res<ButtonInput<KeyCode>>,
+    query_pressed: Query<(&Interaction, &SettingsButton), Changed<Interaction>>,
+    query_buttons: Query<(&SettingsButton, &Children)>,
+    mut query_text: Query<&mut Text>,
+    mut settings: ResMut<Persistent<Settings>>,
+    mut next_settings_state: ResMut<NextState<SettingsState>>,
+) {
+    if keys.just_pressed(KeyCode::Escape) {
+        next_settings_state.set(SettingsState::Closed);
+    }
+
+    for (interaction, button) in query_pressed.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        if let SettingsButton::Back = button {
+            next_settings_state.set(SettingsState::Closed);
+            continue;
+        }
+
+        button.change(&mut settings);
+        settings
+            .persist()
+            .unwrap_or_else(|e| println!("Failed to persist settings: {}", e));
+
+        for (button, children) in query_buttons.iter() {
+            let mut iter = query_text.iter_many_mut(children);
+            while let Some(mut text) = iter.fetch_next() {
+                text.sections[0].value = button.label(&settings);
+            }
+        }
+    }
+}
+
+fn apply_settings(
+    mut commands: Commands,
+    settings: Res<Persistent<Settings>>,
+    mut query_window: Query<(&mut Window, &mut Persistent<WindowState>), With<PrimaryWindow>>,
+    mut query_camera: Query<(Entity, &mut Tonemapping), With<Camera>>,
+    mut global_volume: ResMut<GlobalVolume>,
+    mut ui_scale: ResMut<UiScale>,
+) {
+    for (mut window, mut window_state) in query_window.iter_mut() {
+        // Goes through the persisted window state, which would otherwise undo the change.
+        let mode = if settings.fullscreen {
+            WindowMode::BorderlessFullscreen
+        } else {
+            WindowMode::Windowed
+        };
+        if window_state.mode != mode {
+            window_state.mode = mode;
+        }
+        window.present_mode = if settings.vsync {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::AutoNoVsync
+        };
+    }
+
+    for (entity, mut tonemapping) in query_camera.iter_mut() {
+        *tonemapping = settings.tonemapping.tonemapping();
+        if settings.bloom {
+            commands.entity(entity).insert(BLOOM.clone());
+        } else {
+            commands.entity(entity).remove::<BloomSettings>();
+        }
+    }
+
+    *global_volume = GlobalVolume::new(settings.volume);
+    ui_scale.0 = settings.ui_scale;
+}
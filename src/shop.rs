@@ -0,0 +1,427 @@
+use crate::player::CenterPlayer;
+use crate::ui::{
+    back_to_menu, despawn_screen, screen_root, spawn_button, text, title, BackButton, TEXT_COLOR,
+    TITLE_COLOR,
+};
+use crate::world::coin::RunCoins;
+use crate::{get_state_directory, FontAssets, GameState, MaterialHandles};
+use bevy::app::App;
+use bevy::color::Alpha;
+use bevy::hierarchy::{BuildChildren, ChildBuilder, Children};
+use bevy::prelude::{
+    default, in_state, resource_changed, AlignItems, Assets, Changed, Color, ColorMaterial,
+    Commands, Component, FlexDirection, Gizmos, Interaction, IntoSystemConfigs, NodeBundle,
+    OnEnter, OnExit, Plugin, Query, Res, ResMut, Resource, Style, Text, Transform, UiRect, Update,
+    Val, Vec2, With, Without,
+};
+use bevy_persistent::{Persistent, StorageFormat};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+
+static PALETTES: &[Palette] = &[
+    Palette::Classic,
+    Palette::Ocean,
+    Palette::Forest,
+    Palette::Sunset,
+    Palette::Ghost,
+];
+static EDGE_STYLES: &[EdgeStyle] = &[EdgeStyle::Solid, EdgeStyle::Matching, EdgeStyle::Glowing];
+static TRAILS: &[Trail] = &[Trail::None, Trail::Ribbon, Trail::Sparkles, Trail::Rainbow];
+static TRAIL_LENGTH: usize = 30;
+/// How far the blob moves before the trail gets another point.
+static TRAIL_SPACING: f32 = 4.;
+
+pub struct ShopPlugin;
+
+impl Plugin for ShopPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(TrailPoints::default())
+            .add_systems(OnExit(GameState::InGame), bank_coins)
+            .add_systems(OnEnter(GameState::InGame), clear_trail)
+            .add_systems(OnEnter(GameState::Shop), create_shop_screen)
+            .add_systems(OnExit(GameState::Shop), despawn_screen::<ShopScreen>)
+            .add_systems(
+                Update,
+                (back_to_menu, shop_action).run_if(in_state(GameState::Shop)),
+            )
+            .add_systems(
+                Update,
+                apply_palette.run_if(resource_changed::<Persistent<Wallet>>),
+            )
+            .add_systems(Update, draw_trail.run_if(in_state(GameState::InGame)))
+            .insert_resource(
+                Persistent::<Wallet>::builder()
+                    .name("wallet")
+                    .format(StorageFormat::Json)
+                    .path(get_state_directory().join("wallet.json"))
+                    .default(Wallet::default())
+                    .revert_to_default_on_deserialization_errors(true)
+                    .build()
+                    .expect("Failed to initialize wallet"),
+            );
+    }
+}
+
+/// Colours of the blob's edge, its inside, and its inside while it can jump again.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug, Serialize, Deserialize)]
+pub enum Palette {
+    #[default]
+    Classic,
+    Ocean,
+    Forest,
+    Sunset,
+    Ghost,
+}
+
+impl Palette {
+    fn name(&self) -> &'static str {
+        match self {
+            Palette::Classic => "Classic",
+            Palette::Ocean => "Ocean",
+            Palette::Forest => "Forest",
+            Palette::Sunset => "Sunset",
+            Palette::Ghost => "Ghost",
+        }
+    }
+
+    fn colors(&self) -> [Color; 3] {
+        match self {
+            Palette::Classic => [
+                Color::srgb(0.1, 0.1, 0.1),
+                Color::srgb(1., 0., 0.),
+                Color::srgb(4., 0., 0.),
+            ],
+            Palette::Ocean => [
+                Color::srgb(0.05, 0.1, 0.2),
+                Color::srgb(0., 0.5, 1.),
+                Color::srgb(0., 2., 4.),
+            ],
+            Palette::Forest => [
+                Color::srgb(0.1, 0.15, 0.05),
+                Color::srgb(0.2, 0.8, 0.1),
+                Color::srgb(0.8, 3.2, 0.4),
+            ],
+            Palette::Sunset => [
+                Color::srgb(0.25, 0.05, 0.15),
+                Color::srgb(1., 0.5, 0.),
+                Color::srgb(4., 2., 0.),
+            ],
+            Palette::Ghost => [
+                Color::srgb(0.6, 0.6, 0.7),
+                Color::srgb(0.9, 0.9, 1.),
+                Color::srgb(3., 3., 3.5),
+            ],
+        }
+    }
+}
+
+/// What the squares around the edge of the blob look like.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug, Serialize, Deserialize)]
+pub enum EdgeStyle {
+    /// The palette's own edge colour.
+    #[default]
+    Solid,
+    /// The same colour as the inside, so the blob has no outline.
+    Matching,
+    /// Always lit up.
+    Glowing,
+}
+
+impl EdgeStyle {
+    fn name(&self) -> &'static str {
+        match self {
+            EdgeStyle::Solid => "Solid",
+            EdgeStyle::Matching => "Matching",
+            EdgeStyle::Glowing => "Glowing",
+        }
+    }
+}
+
+/// What the blob leaves behind as it flies.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug, Serialize, Deserialize)]
+pub enum Trail {
+    #[default]
+    None,
+    Ribbon,
+    Sparkles,
+    Rainbow,
+}
+
+impl Trail {
+    fn name(&self) -> &'static str {
+        match self {
+            Trail::None => "None",
+            Trail::Ribbon => "Ribbon",
+            Trail::Sparkles => "Sparkles",
+            Trail::Rainbow => "Rainbow",
+        }
+    }
+}
+
+/// Something that can be bought in the shop.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum Item {
+    Palette(Palette),
+    Edge(EdgeStyle),
+    Trail(Trail),
+}
+
+impl Item {
+    fn name(&self) -> &'static str {
+        match self {
+            Item::Palette(palette) => palette.name(),
+            Item::Edge(edge) => edge.name(),
+            Item::Trail(trail) => trail.name(),
+        }
+    }
+
+    fn price(&self) -> u32 {
+        match self {
+            Item::Palette(Palette::Classic)
+            | Item::Edge(EdgeStyle::Solid)
+            | Item::Trail(Trail::None) => 0,
+            Item::Palette(Palette::Ocean | Palette::Forest) => 50,
+            Item::Palette(Palette::Sunset) => 100,
+            Item::Palette(Palette::Ghost) => 150,
+            Item::Edge(EdgeStyle::Matching) => 30,
+            Item::Edge(EdgeStyle::Glowing) => 80,
+            Item::Trail(Trail::Ribbon | Trail::Sparkles) => 40,
+            Item::Trail(Trail::Rainbow) => 120,
+        }
+    }
+}
+
+/// Coins banked over every run, and what they were spent on.
+#[derive(Resource, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Wallet {
+    pub coins: u32,
+    pub unlocked: HashSet<Item>,
+    pub palette: Palette,
+    pub edge: EdgeStyle,
+    pub trail: Trail,
+}
+
+impl Wallet {
+    fn owns(&self, item: Item) -> bool {
+        item.price() == 0 || self.unlocked.contains(&item)
+    }
+
+    fn is_selected(&self, item: Item) -> bool {
+        match item {
+            Item::Palette(palette) => self.palette == palette,
+            Item::Edge(edge) => self.edge == edge,
+            Item::Trail(trail) => self.trail == trail,
+        }
+    }
+
+    fn select(&mut self, item: Item) {
+        match item {
+            Item::Palette(palette) => self.palette = palette,
+            Item::Edge(edge) => self.edge = edge,
+            Item::Trail(trail) => self.trail = trail,
+        }
+    }
+
+    fn label(&self, item: Item) -> String {
+        if self.is_selected(item) {
+            format!("> {} <", item.name())
+        } else if self.owns(item) {
+            item.name().to_owned()
+        } else {
+            format!("{} - {}", item.name(), item.price())
+        }
+    }
+}
+
+/// Recent positions of the blob's center, newest first.
+#[derive(Resource, Default)]
+struct TrailPoints(VecDeque<Vec2>);
+
+#[derive(Component)]
+struct ShopScreen;
+
+#[derive(Component)]
+struct CoinsText;
+
+fn bank_coins(run_coins: Res<RunCoins>, mut wallet: ResMut<Persistent<Wallet>>) {
+    if run_coins.0 == 0 {
+        return;
+    }
+    wallet.coins += run_coins.0;
+
+    wallet
+        .persist()
+        .unwrap_or_else(|e| println!("Failed to persist wallet: {}", e));
+}
+
+fn apply_palette(
+    wallet: Res<Persistent<Wallet>>,
+    material_handles: Res<MaterialHandles>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let [edge, inside, lit] = wallet.palette.colors();
+    let edge = match wallet.edge {
+        EdgeStyle::Solid => edge,
+        EdgeStyle::Matching => inside,
+        EdgeStyle::Glowing => lit,
+    };
+
+    for (handle, color) in [
+        (&material_handles.black, edge),
+        (&material_handles.red, inside),
+        (&material_handles.bright_red, lit),
+    ] {
+        if let Some(material) = materials.get_mut(handle) {
+            material.color = color;
+        }
+    }
+}
+
+fn clear_trail(mut trail_points: ResMut<TrailPoints>) {
+    trail_points.0.clear();
+}
+
+fn draw_trail(
+    wallet: Res<Persistent<Wallet>>,
+    query_player: Query<&Transform, With<CenterPlayer>>,
+    mut trail_points: ResMut<TrailPoints>,
+    mut gizmos: Gizmos,
+) {
+    if wallet.trail == Trail::None {
+        return;
+    }
+    let Ok(transform) = query_player.get_single() else {
+        return;
+    };
+
+    let position = transform.translation.truncate();
+    if !trail_points
+        .0
+        .front()
+        .is_some_and(|last| last.distance(position) <= TRAIL_SPACING)
+    {
+        trail_points.0.push_front(position);
+        trail_points.0.truncate(TRAIL_LENGTH);
+    }
+
+    let [_, inside, lit] = wallet.palette.colors();
+    let fade = |i: usize| 1. - i as f32 / TRAIL_LENGTH as f32;
+    let points = trail_points.0.iter().copied().enumerate();
+    match wallet.trail {
+        Trail::None => {}
+        Trail::Ribbon => {
+            gizmos.linestrip_gradient_2d(
+                points.map(|(i, point)| (point, inside.with_alpha(fade(i)))),
+            );
+        }
+        Trail::Sparkles => {
+            for (i, point) in points.step_by(3) {
+                gizmos.circle_2d(point, 2. * fade(i), lit.with_alpha(fade(i)));
+            }
+        }
+        Trail::Rainbow => {
+            gizmos.linestrip_gradient_2d(
+                points.map(|(i, point)| (point, Color::hsla(i as f32 * 12., 1., 0.6, fade(i)))),
+            );
+        }
+    }
+}
+
+fn create_shop_screen(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    wallet: Res<Persistent<Wallet>>,
+) {
+    let column = || NodeBundle {
+        style: Style {
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            margin: UiRect::horizontal(Val::Px(20.)),
+            ..default()
+        },
+        ..default()
+    };
+    let section = |parent: &mut ChildBuilder, heading: &str, items: Vec<Item>| {
+        parent.spawn(column()).with_children(|parent| {
+            parent.spawn(text(&fonts, heading, 20., TITLE_COLOR).with_style(Style {
+                margin: UiRect::vertical(Val::Px(10.)),
+                ..default()
+            }));
+            for item in items {
+                spawn_button(parent, &fonts, wallet.label(item), item);
+            }
+        });
+    };
+
+    commands
+        .spawn((screen_root(), ShopScreen))
+        .with_children(|parent| {
+            parent.spawn(title(&fonts, "Shop"));
+            parent.spawn((
+                text(&fonts, coins_label(&wallet), 24., TEXT_COLOR).with_style(Style {
+                    margin: UiRect::bottom(Val::Px(10.)),
+                    ..default()
+                }),
+                CoinsText,
+            ));
+            parent.spawn(NodeBundle::default()).with_children(|parent| {
+                section(
+                    parent,
+                    "Colours",
+                    PALETTES.iter().copied().map(Item::Palette).collect(),
+                );
+                section(
+                    parent,
+                    "Edges",
+                    EDGE_STYLES.iter().copied().map(Item::Edge).collect(),
+                );
+                section(
+                    parent,
+                    "Trails",
+                    TRAILS.iter().copied().map(Item::Trail).collect(),
+                );
+            });
+            spawn_button(parent, &fonts, "Back", BackButton);
+        });
+}
+
+fn coins_label(wallet: &Wallet) -> String {
+    format!("Coins {}", wallet.coins)
+}
+
+/// Selects an owned item, or buys it first if the wallet holds enough coins.
+fn shop_action(
+    query_pressed: Query<(&Interaction, &Item), Changed<Interaction>>,
+    query_buttons: Query<(&Item, &Children)>,
+    mut query_text: Query<&mut Text, Without<CoinsText>>,
+    mut query_coins_text: Query<&mut Text, With<CoinsText>>,
+    mut wallet: ResMut<Persistent<Wallet>>,
+) {
+    for (interaction, item) in query_pressed.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        if !wallet.owns(*item) {
+            if wallet.coins < item.price() {
+                continue;
+            }
+            wallet.coins -= item.price();
+            wallet.unlocked.insert(*item);
+        }
+        wallet.select(*item);
+        wallet
+            .persist()
+            .unwrap_or_else(|e| println!("Failed to persist wallet: {}", e));
+
+        for (item, children) in query_buttons.iter() {
+            let mut iter = query_text.iter_many_mut(children);
+            while let Some(mut text) = iter.fetch_next() {
+                text.sections[0].value = wallet.label(*item);
+            }
+        }
+        for mut text in query_coins_text.iter_mut() {
+            text.sections[0].value = coins_label(&wallet);
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use crate::events::{DeathCause, Died, Jumped};
+use crate::score::Score;
+use crate::ui::{
+    back_to_menu, despawn_screen, format_duration, screen_root, spawn_button, text, title,
+    BackButton, TEXT_COLOR,
+};
+use crate::{
+    end_run, get_state_directory, FontAssets, GameMode, GameState, InRun, PauseState, Run,
+};
+use bevy::app::App;
+use bevy::hierarchy::BuildChildren;
+use bevy::prelude::{
+    in_state, not, resource_equals, Commands, Component, EventReader, FixedUpdate,
+    IntoSystemConfigs, OnEnter, OnExit, Plugin, Res, ResMut, Resource, Update,
+};
+use bevy_persistent::{Persistent, StorageFormat};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::GameOver), record_stats)
+            .add_systems(OnExit(InRun), save_stats)
+            .add_systems(OnEnter(GameState::Stats), create_stats_screen)
+            .add_systems(OnExit(GameState::Stats), despawn_screen::<StatsScreen>)
+            .add_systems(Update, back_to_menu.run_if(in_state(GameState::Stats)))
+            .add_systems(
+                FixedUpdate,
+                count_jumps_and_deaths
+                    .before(end_run)
+                    .run_if(in_state(PauseState::Running))
+                    .run_if(not(resource_equals(GameMode::Playtest))),
+            )
+            .insert_resource(
+                Persistent::<Stats>::builder()
+                    .name("stats")
+                    .format(StorageFormat::Json)
+                    .path(get_state_directory().join("stats.json"))
+                    .default(Stats::default())
+                    .revert_to_default_on_deserialization_errors(true)
+                    .build()
+                    .expect("Failed to initialize stats"),
+            );
+    }
+}
+
+/// Lifetime totals over every finished run.
+#[derive(Default, Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Stats {
+    pub games_played: u32,
+    pub total_jumps: u32,
+    pub total_play_time: Duration,
+    pub total_height_climbed: f32,
+    pub total_score: u64,
+    pub longest_run: Duration,
+    pub deaths_below_line: u32,
+    pub deaths_out_of_bounds: u32,
+}
+
+impl Stats {
+    pub fn average_score(&self) -> u32 {
+        if self.games_played == 0 {
+            0
+        } else {
+            (self.total_score / self.games_played as u64) as u32
+        }
+    }
+}
+
+#[derive(Component)]
+struct StatsScreen;
+
+fn count_jumps_and_deaths(
+    mut jumped_events: EventReader<Jumped>,
+    mut died_events: EventReader<Died>,
+    mut stats: ResMut<Persistent<Stats>>,
+) {
+    stats.total_jumps += jumped_events.read().count() as u32;
+    for died in died_events.read() {
+        match died.cause {
+            DeathCause::FellBelowLine => stats.deaths_below_line += 1,
+            DeathCause::OutOfBounds => stats.deaths_out_of_bounds += 1,
+        }
+    }
+}
+
+fn record_stats(score: Res<Score>, run: Res<Run>, mut stats: ResMut<Persistent<Stats>>) {
+    stats.games_played += 1;
+    stats.total_play_time += run.duration;
+    stats.total_height_climbed += run.max_height.max(0.);
+    stats.total_score += score.0 as u64;
+    stats.longest_run = stats.longest_run.max(run.duration);
+
+    stats
+        .persist()
+        .unwrap_or_else(|e| println!("Failed to persist stats: {}", e));
+}
+
+/// Keeps the jumps of runs that were quit or restarted from the pause menu.
+fn save_stats(stats: Res<Persistent<Stats>>) {
+    stats
+        .persist()
+        .unwrap_or_else(|e| println!("Failed to persist stats: {}", e));
+}
+
+fn create_stats_screen(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    stats: Res<Persistent<Stats>>,
+) {
+    let lines = [
+        format!("Games played {}", stats.games_played),
+        format!("Jumps {}", stats.total_jumps),
+        format!("Play time {}", format_duration(stats.total_play_time)),
+        format!("Height climbed {:.0}m", stats.total_height_climbed),
+        format!("Longest run {}", format_duration(stats.longest_run)),
+        format!("Average score {}", stats.average_score()),
+        format!("Fell below the line {}", stats.deaths_below_line),
+        format!("Went out of bounds {}", stats.deaths_out_of_bounds),
+    ];
+
+    commands
+        .spawn((screen_root(), StatsScreen))
+        .with_children(|parent| {
+            parent.spawn(title(&fonts, "Stats"));
+            for line in lines {
+                parent.spawn(text(&fonts, line, 20., TEXT_COLOR));
+            }
+            spawn_button(parent, &fonts, "Back", BackButton);
+        });
+}
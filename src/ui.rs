@@ -0,0 +1,135 @@
+use crate::{FontAssets, GameState};
+use bevy::hierarchy::{BuildChildren, ChildBuilder, DespawnRecursiveExt};
+use bevy::input::ButtonInput;
+use bevy::prelude::{
+    default, AlignItems, BackgroundColor, Button, ButtonBundle, Changed, Color, Commands,
+    Component, Entity, FlexDirection, Interaction, JustifyContent, KeyCode, NextState, NodeBundle,
+    Query, Res, ResMut, Style, TextBundle, TextStyle, UiRect, Val, With,
+};
+use std::time::Duration;
+
+pub static TITLE_COLOR: Color = Color::srgb(1., 1., 0.);
+pub static TEXT_COLOR: Color = Color::WHITE;
+static BUTTON_COLOR: Color = Color::srgba(0., 0., 0., 0.6);
+static BUTTON_HOVERED_COLOR: Color = Color::srgba(0.3, 0.3, 0.3, 0.8);
+static BUTTON_PRESSED_COLOR: Color = Color::srgba(0.5, 0.5, 0., 0.8);
+
+/// Full screen, dimmed column that menus and info screens are laid out in.
+pub fn screen_root() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            width: Val::Percent(100.),
+            height: Val::Percent(100.),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        background_color: Color::srgba(0., 0., 0., 0.5).into(),
+        ..default()
+    }
+}
+
+pub fn text(
+    fonts: &FontAssets,
+    value: impl Into<String>,
+    font_size: f32,
+    color: Color,
+) -> TextBundle {
+    TextBundle::from_section(
+        value,
+        TextStyle {
+            font: fonts.segmental.clone(),
+            font_size,
+            color,
+        },
+    )
+}
+
+pub fn title(fonts: &FontAssets, value: impl Into<String>) -> TextBundle {
+    text(fonts, value, 40., TITLE_COLOR).with_style(Style {
+        margin: UiRect::bottom(Val::Px(20.)),
+        ..default()
+    })
+}
+
+/// Spawns a menu button labelled `label`, tagged with `action` so a menu can tell its buttons apart.
+pub fn spawn_button(
+    parent: &mut ChildBuilder,
+    fonts: &FontAssets,
+    label: impl Into<String>,
+    action: impl Component,
+) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(240.),
+                    padding: UiRect::all(Val::Px(8.)),
+                    margin: UiRect::all(Val::Px(5.)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: BUTTON_COLOR.into(),
+                ..default()
+            },
+            action,
+        ))
+        .with_children(|button| {
+            button.spawn(text(fonts, label, 24., TEXT_COLOR));
+        });
+}
+
+type ChangedButton = (Changed<Interaction>, With<Button>);
+
+pub fn highlight_buttons(
+    mut query_buttons: Query<(&Interaction, &mut BackgroundColor), ChangedButton>,
+) {
+    for (interaction, mut background_color) in query_buttons.iter_mut() {
+        *background_color = match interaction {
+            Interaction::Pressed => BUTTON_PRESSED_COLOR,
+            Interaction::Hovered => BUTTON_HOVERED_COLOR,
+            Interaction::None => BUTTON_COLOR,
+        }
+        .into();
+    }
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    if seconds >= 3600 {
+        format!(
+            "{}:{:02}:{:02}",
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        )
+    } else {
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    }
+}
+
+pub fn despawn_screen<T: Component>(mut commands: Commands, query_screen: Query<Entity, With<T>>) {
+    for entity in query_screen.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Returns to the main menu from the screen it is on.
+#[derive(Component)]
+pub struct BackButton;
+
+pub fn back_to_menu(
+    mut next_state: ResMut<NextState<GameState>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    query_buttons: Query<&Interaction, (Changed<Interaction>, With<BackButton>)>,
+) {
+    if keys.just_pressed(KeyCode::Escape)
+        || query_buttons
+            .iter()
+            .any(|interaction| *interaction == Interaction::Pressed)
+    {
+        next_state.set(GameState::MainMenu);
+    }
+}
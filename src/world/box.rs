@@ -1,17 +1,73 @@
-use crate::ImageAssets;
+use crate::drag::Throw;
+use crate::player::{jump_velocity, Player};
+use crate::{GameMode, GameState, Height, ImageAssets, PauseState, HALF_WORLD_SIZE};
 use avian2d::collision::Collider;
-use avian2d::prelude::{AngularVelocity, LinearVelocity, RigidBody};
-use bevy::prelude::{default, Bundle, Component, Res, SpriteBundle, Transform, Vec3};
+use avian2d::position::{Position, Rotation};
+use avian2d::prelude::{
+    AngularVelocity, ColliderDensity, Collisions, Joint, LinearVelocity, RevoluteJoint, RigidBody,
+};
+use bevy::app::App;
+use bevy::input::ButtonInput;
+use bevy::prelude::{
+    default, in_state, Bundle, Camera, Commands, Component, Entity, EventReader, FixedUpdate,
+    GlobalTransform, Handle, Image, IntoSystemConfigs, KeyCode, OnEnter, Plugin, Query, Res,
+    ResMut, Resource, Sprite, SpriteBundle, Time, Timer, TimerMode, Transform, Vec2, Vec3, With,
+};
 use rand::Rng;
-use crate::world::WorldEntity;
+use std::time::Duration;
+use crate::world::{WorldEntity, WorldRng};
+
+pub static BOX_SIZE: f32 = 20.;
+/// Line height from which on crates start falling.
+static HAZARD_HEIGHT: f32 = 500.;
+/// Line height at which crates fall most often and are at their biggest.
+static HARDEST_HEIGHT: f32 = 6000.;
+/// Light enough that the blob can still jump while carrying one.
+static LIGHT_BOX_DENSITY: f32 = 0.2;
+static MAX_THROW_DRAG: f32 = 120.;
+static THROW_BOOST: f32 = 1.5;
+
+pub struct BoxesPlugin;
+
+impl Plugin for BoxesPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(HazardTimer(Timer::from_seconds(8., TimerMode::Repeating)))
+            .insert_resource(HeldBox {
+                held: None,
+                thrown: false,
+            })
+            .add_systems(OnEnter(GameState::InGame), (reset_hazard_timer, drop_held_box))
+            .add_systems(
+                FixedUpdate,
+                drop_crates.run_if(has_hazards).run_if(in_state(PauseState::Running)),
+            )
+            .add_systems(
+                FixedUpdate,
+                (hold_boxes, throw_boxes).run_if(in_state(PauseState::Running)),
+            );
+    }
+}
 
 #[derive(Component)]
-struct Box;
+pub struct Box;
+
+/// Time until the next crate falls from above.
+#[derive(Resource)]
+struct HazardTimer(Timer);
+
+/// The box the blob holds on to while E is held, and the joint it is held by.
+#[derive(Resource)]
+pub struct HeldBox {
+    held: Option<(Entity, Entity)>,
+    /// Set once the box is thrown, so it isn't grabbed again until E is let go of.
+    thrown: bool,
+}
 
 #[derive(Bundle)]
-struct BoxBundle {
+pub struct BoxBundle {
     rigid_body: RigidBody,
     collider: Collider,
+    collider_density: ColliderDensity,
     sprite: SpriteBundle,
     r#box: Box,
     linear_velocity: LinearVelocity,
@@ -20,18 +76,24 @@ struct BoxBundle {
 }
 
 impl BoxBundle {
-    fn new(
+    pub fn new(
         translation: Vec3,
+        size: f32,
         linear_velocity: LinearVelocity,
         angular_velocity: AngularVelocity,
-        images: Res<ImageAssets>,
+        texture: Handle<Image>,
     ) -> Self {
         Self {
             rigid_body: RigidBody::Dynamic,
-            collider: Collider::rectangle(20.0, 20.0),
+            collider: Collider::rectangle(size, size),
+            collider_density: ColliderDensity::default(),
             sprite: SpriteBundle {
+                sprite: Sprite {
+                    custom_size: Some(Vec2::splat(size)),
+                    ..default()
+                },
                 transform: Transform::from_translation(translation),
-                texture: images.boxes[rand::thread_rng().gen_range(0..images.boxes.len())].clone(),
+                texture,
                 ..default()
             },
             r#box: Box,
@@ -40,4 +102,140 @@ impl BoxBundle {
             world_entity: WorldEntity,
         }
     }
+
+    /// Makes the box light enough to be carried around, for boxes placed to be picked up.
+    pub fn light(mut self) -> Self {
+        self.collider_density = ColliderDensity(LIGHT_BOX_DENSITY);
+        self
+    }
+}
+
+fn has_hazards(mode: Res<GameMode>) -> bool {
+    mode.is_procedural() && *mode != GameMode::Zen
+}
+
+fn reset_hazard_timer(mut hazard_timer: ResMut<HazardTimer>) {
+    hazard_timer.0.reset();
+}
+
+fn drop_crates(
+    mut commands: Commands,
+    images: Res<ImageAssets>,
+    query_camera: Query<(&Camera, &GlobalTransform)>,
+    height: Res<Height>,
+    time: Res<Time>,
+    mut hazard_timer: ResMut<HazardTimer>,
+    mut world_rng: ResMut<WorldRng>,
+) {
+    if height.0 < HAZARD_HEIGHT {
+        return;
+    }
+    let difficulty = ((height.0 - HAZARD_HEIGHT) / (HARDEST_HEIGHT - HAZARD_HEIGHT)).clamp(0., 1.);
+
+    hazard_timer.0.set_duration(Duration::from_secs_f32(8. - 6. * difficulty));
+    if !hazard_timer.0.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let (camera, camera_transform) = query_camera.single();
+    let window_top = camera
+        .viewport_to_world_2d(camera_transform, Vec2::ZERO)
+        .unwrap_or(Vec2::ZERO)
+        .y;
+
+    let rng = &mut world_rng.0;
+    let size = rng.gen_range(14. ..20.) * (1. + difficulty);
+    let max_x = HALF_WORLD_SIZE - size;
+    commands.spawn(BoxBundle::new(
+        Vec3::new(rng.gen_range(-max_x..max_x), window_top + size, 0.),
+        size,
+        LinearVelocity(Vec2::new(0., -100.)),
+        AngularVelocity(rng.gen_range(-2. ..2.)),
+        images.boxes[rng.gen_range(0..images.boxes.len())].clone(),
+    ));
+}
+
+impl HeldBox {
+    pub fn release(&mut self, commands: &mut Commands) {
+        if let Some((_, joint)) = self.held.take() {
+            if let Some(mut joint) = commands.get_entity(joint) {
+                joint.despawn();
+            }
+        }
+    }
+}
+
+fn drop_held_box(mut commands: Commands, mut held_box: ResMut<HeldBox>) {
+    held_box.release(&mut commands);
+    held_box.thrown = true;
+}
+
+/// Pins a box the blob touches to the square touching it, for as long as E is held.
+fn hold_boxes(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    collisions: Res<Collisions>,
+    query_player: Query<&Position, With<Player>>,
+    query_box: Query<(&Position, &Rotation), With<Box>>,
+    mut held_box: ResMut<HeldBox>,
+) {
+    if !keys.pressed(KeyCode::KeyE) {
+        held_box.release(&mut commands);
+        held_box.thrown = false;
+        return;
+    }
+    if held_box.held.is_some() || held_box.thrown {
+        return;
+    }
+
+    let touching = collisions.iter().find_map(|contacts| {
+        if !contacts.during_current_frame {
+            return None;
+        }
+        if query_player.contains(contacts.entity1) && query_box.contains(contacts.entity2) {
+            Some((contacts.entity1, contacts.entity2))
+        } else if query_player.contains(contacts.entity2) && query_box.contains(contacts.entity1) {
+            Some((contacts.entity2, contacts.entity1))
+        } else {
+            None
+        }
+    });
+    let Some((square, r#box)) = touching else {
+        return;
+    };
+    let (Ok(square_position), Ok((box_position, box_rotation))) =
+        (query_player.get(square), query_box.get(r#box))
+    else {
+        return;
+    };
+
+    let anchor = box_rotation.inverse() * (square_position.0 - box_position.0);
+    let joint = commands
+        .spawn((
+            RevoluteJoint::new(square, r#box).with_local_anchor_2(anchor),
+            WorldEntity,
+        ))
+        .id();
+    held_box.held = Some((r#box, joint));
+}
+
+/// Lets go of the held box, sending it off along the drag.
+fn throw_boxes(
+    mut commands: Commands,
+    mut throw_events: EventReader<Throw>,
+    mut query_velocity: Query<&mut LinearVelocity, With<Box>>,
+    mut held_box: ResMut<HeldBox>,
+) {
+    let Some(throw) = throw_events.read().last() else {
+        return;
+    };
+    let Some((r#box, _)) = held_box.held else {
+        return;
+    };
+
+    if let Ok(mut velocity) = query_velocity.get_mut(r#box) {
+        velocity.0 = jump_velocity(throw.drag.clamp_length_max(MAX_THROW_DRAG)) * THROW_BOOST;
+    }
+    held_box.release(&mut commands);
+    held_box.thrown = true;
 }
@@ -0,0 +1,192 @@
+use crate::player::Player;
+use crate::world::WorldEntity;
+use crate::{FontAssets, GameState, ImageAssets, PauseState};
+use avian2d::collision::Collider;
+use avian2d::prelude::{CollisionStarted, RigidBody, Sensor};
+use bevy::app::App;
+use bevy::hierarchy::{BuildChildren, DespawnRecursiveExt};
+use bevy::prelude::{
+    default, in_state, Camera, Color, Commands, Component, Entity, EventReader, FixedUpdate,
+    GlobalTransform, IntoSystemConfigs, OnEnter, OnExit, Plugin, Query, Res, ResMut, Resource,
+    Sprite, SpriteBundle, Text, Text2dBundle, TextStyle, Transform, Update, Vec2, With,
+};
+use bevy::sprite::Anchor;
+use rand::Rng;
+use std::f32::consts::PI;
+
+static COIN_SIZE: f32 = 12.;
+/// How far above the straight line between two platforms the arc of coins peaks.
+static ARC_HEIGHT: f32 = 30.;
+/// Where the coin counter sits, in viewport coordinates, just below the score.
+static COUNTER_POSITION: Vec2 = Vec2::new(18., 56.);
+
+pub struct CoinsPlugin;
+
+impl Plugin for CoinsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(RunCoins(0))
+            .add_systems(OnEnter(GameState::InGame), create_coin_counter)
+            .add_systems(OnExit(GameState::InGame), remove_coin_counter)
+            .add_systems(
+                Update,
+                scroll_coin_counter.run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(
+                FixedUpdate,
+                collect_coins.run_if(in_state(PauseState::Running)),
+            );
+    }
+}
+
+#[derive(Component)]
+pub struct Coin;
+
+/// Coins picked up this run, banked into the wallet when it ends.
+#[derive(Resource)]
+pub struct RunCoins(pub u32);
+
+/// The coin icon in the corner, with the run's count next to it.
+#[derive(Component)]
+struct CoinCounter;
+
+#[derive(Component)]
+struct CoinCounterText;
+
+/// Lays a few coins along an arc from one platform to the next, where the blob would jump.
+pub fn spawn_coins(
+    commands: &mut Commands,
+    images: &ImageAssets,
+    from: Vec2,
+    to: Vec2,
+    count: u32,
+) {
+    for i in 1..=count {
+        let t = i as f32 / (count + 1) as f32;
+        let pos = from.lerp(to, t) + Vec2::new(0., COIN_SIZE + (t * PI).sin() * ARC_HEIGHT);
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    custom_size: Some(Vec2::splat(COIN_SIZE)),
+                    ..default()
+                },
+                transform: Transform::from_translation(pos.extend(5.)),
+                texture: images.coin.clone(),
+                ..default()
+            },
+            RigidBody::Static,
+            Collider::circle(COIN_SIZE / 2.),
+            Sensor,
+            Coin,
+            WorldEntity,
+        ));
+    }
+}
+
+/// How many coins to put between two generated platforms, if any.
+pub fn roll_coins(rng: &mut impl Rng) -> u32 {
+    if rng.gen_bool(0.35) {
+        rng.gen_range(2..=4)
+    } else {
+        0
+    }
+}
+
+fn collect_coins(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionStarted>,
+    query_player: Query<(), With<Player>>,
+    query_coin: Query<(), With<Coin>>,
+    mut run_coins: ResMut<RunCoins>,
+    mut query_counter: Query<&mut Text, With<CoinCounterText>>,
+) {
+    let mut collected: Vec<Entity> = Vec::new();
+    for CollisionStarted(entity1, entity2) in collision_events.read() {
+        let coin = if query_player.contains(*entity1) {
+            *entity2
+        } else if query_player.contains(*entity2) {
+            *entity1
+        } else {
+            continue;
+        };
+        // Several squares can touch the same coin at once.
+        if query_coin.contains(coin) && !collected.contains(&coin) {
+            collected.push(coin);
+        }
+    }
+    if collected.is_empty() {
+        return;
+    }
+
+    for coin in collected.iter() {
+        commands.entity(*coin).despawn();
+    }
+    run_coins.0 += collected.len() as u32;
+    for mut text in query_counter.iter_mut() {
+        text.sections[0].value = format!("{}", run_coins.0);
+    }
+}
+
+fn counter_position(query_camera: &Query<(&Camera, &GlobalTransform)>) -> Vec2 {
+    let (camera, camera_transform) = query_camera.single();
+    camera
+        .viewport_to_world_2d(camera_transform, COUNTER_POSITION)
+        .unwrap_or(Vec2::ZERO)
+}
+
+fn create_coin_counter(
+    mut commands: Commands,
+    images: Res<ImageAssets>,
+    fonts: Res<FontAssets>,
+    mut run_coins: ResMut<RunCoins>,
+    query_camera: Query<(&Camera, &GlobalTransform)>,
+) {
+    run_coins.0 = 0;
+
+    commands
+        .spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    custom_size: Some(Vec2::splat(COIN_SIZE * 1.5)),
+                    ..default()
+                },
+                transform: Transform::from_translation(counter_position(&query_camera).extend(10.)),
+                texture: images.coin.clone(),
+                ..default()
+            },
+            CoinCounter,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text2dBundle {
+                    text: Text::from_section(
+                        "0",
+                        TextStyle {
+                            font: fonts.segmental.clone(),
+                            font_size: 22.0,
+                            color: Color::srgb(1.5, 1.2, 0.2),
+                        },
+                    ),
+                    text_anchor: Anchor::CenterLeft,
+                    transform: Transform::from_xyz(COIN_SIZE, 0., 0.),
+                    ..default()
+                },
+                CoinCounterText,
+            ));
+        });
+}
+
+fn remove_coin_counter(mut commands: Commands, query_counter: Query<Entity, With<CoinCounter>>) {
+    for entity in query_counter.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn scroll_coin_counter(
+    query_camera: Query<(&Camera, &GlobalTransform)>,
+    mut query_counter: Query<&mut Transform, With<CoinCounter>>,
+) {
+    let position = counter_position(&query_camera);
+    for mut transform in query_counter.iter_mut() {
+        transform.translation = position.extend(10.);
+    }
+}
@@ -0,0 +1,685 @@
+use crate::ui::{despawn_screen, text, TEXT_COLOR};
+use crate::world::level::{Level, LevelPlatform};
+use crate::world::path::Path;
+use crate::world::platform::{Platform, PLATFORM_WIDTH};
+use crate::world::surface::Surface;
+use crate::world::{spawn_ground, WorldEntity};
+use crate::{start_run, FontAssets, GameMode, GameState, ImageAssets};
+use avian2d::prelude::{DistanceJoint, Physics, PhysicsTime};
+use bevy::app::App;
+use bevy::asset::io::file::FileAssetReader;
+use bevy::asset::AssetServer;
+use bevy::hierarchy::{BuildChildren, Children};
+use bevy::input::mouse::MouseWheel;
+use bevy::input::ButtonInput;
+use bevy::math::Vec2;
+use bevy::prelude::{
+    default, in_state, resource_changed, resource_equals, Camera, Color, Commands, Component,
+    DetectChanges, Entity, EventReader, Gizmos, GlobalTransform, IntoSystemConfigs, KeyCode, Local,
+    MouseButton, NextState, NodeBundle, OnEnter, OnExit, Plugin, PositionType, Query, Res, ResMut,
+    Resource, Style, Text, Time, Transform, UiRect, Update, Val, Window, With,
+};
+use bevy::window::PrimaryWindow;
+use ron::ser::PrettyConfig;
+use std::path::PathBuf;
+
+/// Where the edited level sits in the world, so it lines up with how chunks are spliced in.
+static EDITOR_ORIGIN: Vec2 = Vec2::new(0., -110.);
+static GRID_SIZE: f32 = 5.;
+static SCROLL_SPEED: f32 = 300.;
+/// How close to a selected platform's end a drag resizes it instead of moving it.
+static EDGE_HANDLE_SIZE: f32 = 4.;
+static MIN_PLATFORM_WIDTH: f32 = 20.;
+
+pub struct EditorPlugin;
+
+impl Plugin for EditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(EditorLevel(Level::default()))
+            .insert_resource(EditorTool::Static)
+            .insert_resource(EditorSelection(None))
+            .insert_resource(EditorStatus(String::new()))
+            .add_systems(
+                OnEnter(GameState::Editor),
+                (
+                    load_editor_level,
+                    (pause_physics, create_editor_text, rebuild_editor_world),
+                )
+                    .chain(),
+            )
+            .add_systems(
+                OnExit(GameState::Editor),
+                (
+                    resume_physics,
+                    clear_editor_world,
+                    reset_editor_camera,
+                    despawn_screen::<EditorText>,
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    editor_keys,
+                    editor_mouse,
+                    scroll_editor,
+                    draw_selection,
+                    update_editor_text,
+                    rebuild_editor_world.run_if(resource_changed::<EditorLevel>),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Editor)),
+            )
+            .add_systems(
+                OnEnter(GameState::InGame),
+                create_playtest_level
+                    .after(start_run)
+                    .run_if(resource_equals(GameMode::Playtest)),
+            )
+            .add_systems(
+                Update,
+                stop_playtest
+                    .run_if(in_state(GameState::InGame))
+                    .run_if(resource_equals(GameMode::Playtest)),
+            );
+    }
+}
+
+/// The level being edited, kept apart from the spawned world, which is rebuilt from it on changes.
+#[derive(Resource)]
+struct EditorLevel(Level);
+
+/// What a click on empty space places.
+#[derive(Resource, Clone, Copy, PartialEq)]
+enum EditorTool {
+    Static,
+    Hanging,
+    Moving,
+    Crumbling,
+    OneWay,
+    Seesaw,
+    Spinner,
+    Bridge,
+    Bolt,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Selected {
+    Platform(usize),
+    Bolt(usize),
+}
+
+#[derive(Resource)]
+struct EditorSelection(Option<Selected>);
+
+/// What dragging with the left button does to the selection.
+#[derive(Clone, Copy)]
+enum EditorDrag {
+    /// Moves it, keeping the cursor where it grabbed the item.
+    Move(Vec2),
+    /// Stretches the selected platform to either side, keeping it centered.
+    Resize,
+}
+
+/// Outcome of the last save, shown under the controls.
+#[derive(Resource)]
+struct EditorStatus(String);
+
+#[derive(Component)]
+struct EditorText;
+
+fn editor_level_path() -> PathBuf {
+    FileAssetReader::get_base_path().join("assets/levels/editor.level.ron")
+}
+
+/// Loads the level saved from the editor the first time the editor is opened, keeping edits when
+/// coming back from a playtest. A file that can't be read as a level is moved aside, so saving the
+/// new level doesn't write over it.
+fn load_editor_level(
+    mut loaded: Local<bool>,
+    mut level: ResMut<EditorLevel>,
+    mut status: ResMut<EditorStatus>,
+) {
+    if *loaded {
+        return;
+    }
+    *loaded = true;
+
+    let path = editor_level_path();
+    let Ok(bytes) = std::fs::read(&path) else {
+        return;
+    };
+    match Level::from_bytes(&bytes) {
+        Ok(loaded_level) => level.0 = loaded_level,
+        Err(e) => {
+            let backup = path.with_extension("ron.bak");
+            let message = match std::fs::rename(&path, &backup) {
+                Ok(()) => format!(
+                    "Could not load {}: {}. Moved it to {}",
+                    path.display(),
+                    e,
+                    backup.display()
+                ),
+                Err(rename_error) => format!(
+                    "Could not load {}: {}. Could not move it aside: {}",
+                    path.display(),
+                    e,
+                    rename_error
+                ),
+            };
+            println!("{}", message);
+            status.0 = message;
+        }
+    }
+}
+
+fn snap(position: Vec2) -> Vec2 {
+    (position / GRID_SIZE).round() * GRID_SIZE
+}
+
+impl EditorTool {
+    fn name(&self) -> &'static str {
+        match self {
+            EditorTool::Static => "Static",
+            EditorTool::Hanging => "Hanging",
+            EditorTool::Moving => "Moving",
+            EditorTool::Crumbling => "Crumbling",
+            EditorTool::OneWay => "One-way",
+            EditorTool::Seesaw => "Seesaw",
+            EditorTool::Spinner => "Spinner",
+            EditorTool::Bridge => "Bridge",
+            EditorTool::Bolt => "Bolt",
+        }
+    }
+
+    fn place(&self, level: &mut Level, position: Vec2) -> Selected {
+        let kind = match self {
+            EditorTool::Static => Platform::Static {
+                width: PLATFORM_WIDTH,
+            },
+            EditorTool::Hanging => Platform::Hanging {
+                width: PLATFORM_WIDTH,
+            },
+            EditorTool::Moving => Platform::Moving {
+                path: Path::Horizontal { extent: 40. },
+                period: 8.,
+                phase: 0.,
+                width: PLATFORM_WIDTH,
+            },
+            EditorTool::Crumbling => Platform::Crumbling {
+                width: PLATFORM_WIDTH,
+                delay: 0.8,
+                respawn: Some(3.),
+            },
+            EditorTool::OneWay => Platform::OneWay {
+                width: PLATFORM_WIDTH,
+            },
+            EditorTool::Seesaw => Platform::Seesaw {
+                width: PLATFORM_WIDTH * 1.5,
+            },
+            EditorTool::Spinner => Platform::Spinner {
+                width: PLATFORM_WIDTH * 1.5,
+                speed: 1.,
+            },
+            EditorTool::Bridge => Platform::Bridge {
+                width: PLATFORM_WIDTH * 2.,
+                planks: 5,
+            },
+            EditorTool::Bolt => {
+                level.bolts.push(position);
+                return Selected::Bolt(level.bolts.len() - 1);
+            }
+        };
+        level.platforms.push(LevelPlatform {
+            kind,
+            position,
+            surface: Surface::default(),
+        });
+        Selected::Platform(level.platforms.len() - 1)
+    }
+}
+
+impl Selected {
+    fn position(self, level: &Level) -> Vec2 {
+        match self {
+            Selected::Platform(index) => level.platforms[index].position,
+            Selected::Bolt(index) => level.bolts[index],
+        }
+    }
+
+    fn set_position(self, level: &mut Level, position: Vec2) {
+        match self {
+            Selected::Platform(index) => level.platforms[index].position = position,
+            Selected::Bolt(index) => level.bolts[index] = position,
+        }
+    }
+
+    fn remove(self, level: &mut Level) {
+        match self {
+            Selected::Platform(index) => {
+                level.platforms.remove(index);
+            }
+            Selected::Bolt(index) => {
+                level.bolts.remove(index);
+            }
+        }
+    }
+}
+
+/// The topmost item under `position`, in level coordinates.
+fn item_at(level: &Level, position: Vec2) -> Option<Selected> {
+    let bolt = level
+        .bolts
+        .iter()
+        .rposition(|bolt| bolt.distance(position) <= 8.)
+        .map(Selected::Bolt);
+    bolt.or_else(|| {
+        level
+            .platforms
+            .iter()
+            .rposition(|platform| {
+                let offset = (position - platform.position).abs();
+                offset.x <= platform.kind.width() / 2. && offset.y <= 10.
+            })
+            .map(Selected::Platform)
+    })
+}
+
+/// Whether the position is on the left or right edge of the platform, where dragging resizes it.
+fn on_edge(platform: &LevelPlatform, position: Vec2) -> bool {
+    let offset = (position - platform.position).abs();
+    (offset.x - platform.kind.width() / 2.).abs() <= EDGE_HANDLE_SIZE && offset.y <= 10.
+}
+
+fn cursor_in_level(
+    query_window: &Query<&Window, With<PrimaryWindow>>,
+    query_camera: &Query<(&Camera, &GlobalTransform)>,
+) -> Option<Vec2> {
+    let cursor = query_window.get_single().ok()?.cursor_position()?;
+    let (camera, camera_transform) = query_camera.get_single().ok()?;
+    let world = camera.viewport_to_world_2d(camera_transform, cursor)?;
+    Some(world - EDITOR_ORIGIN)
+}
+
+fn pause_physics(mut physics_time: ResMut<Time<Physics>>) {
+    physics_time.pause();
+}
+
+fn resume_physics(mut physics_time: ResMut<Time<Physics>>) {
+    physics_time.unpause();
+}
+
+fn clear_editor_world(
+    mut commands: Commands,
+    query_world_entity: Query<Entity, With<WorldEntity>>,
+    query_joints: Query<Entity, With<DistanceJoint>>,
+) {
+    for entity in query_world_entity.iter().chain(query_joints.iter()) {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn rebuild_editor_world(
+    mut commands: Commands,
+    images: Res<ImageAssets>,
+    asset_server: Res<AssetServer>,
+    level: Res<EditorLevel>,
+    query_world_entity: Query<Entity, With<WorldEntity>>,
+    query_joints: Query<Entity, With<DistanceJoint>>,
+) {
+    for entity in query_world_entity.iter().chain(query_joints.iter()) {
+        commands.entity(entity).despawn();
+    }
+    spawn_ground(&mut commands, &images);
+    level
+        .0
+        .spawn(&mut commands, &images, &asset_server, EDITOR_ORIGIN);
+}
+
+fn reset_editor_camera(mut query_camera: Query<&mut Transform, With<Camera>>) {
+    for mut transform in query_camera.iter_mut() {
+        *transform = Transform::default();
+    }
+}
+
+fn create_editor_text(mut commands: Commands, fonts: Res<FontAssets>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(10.),
+                    top: Val::Px(10.),
+                    padding: UiRect::all(Val::Px(8.)),
+                    ..default()
+                },
+                background_color: Color::srgba(0., 0., 0., 0.5).into(),
+                ..default()
+            },
+            EditorText,
+        ))
+        .with_children(|parent| {
+            parent.spawn(text(&fonts, "", 14., TEXT_COLOR));
+        });
+}
+
+fn editor_keys(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut level: ResMut<EditorLevel>,
+    mut tool: ResMut<EditorTool>,
+    mut selection: ResMut<EditorSelection>,
+    mut status: ResMut<EditorStatus>,
+    mut mode: ResMut<GameMode>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for (key, key_tool) in [
+        (KeyCode::Digit1, EditorTool::Static),
+        (KeyCode::Digit2, EditorTool::Hanging),
+        (KeyCode::Digit3, EditorTool::Moving),
+        (KeyCode::Digit4, EditorTool::Crumbling),
+        (KeyCode::Digit5, EditorTool::OneWay),
+        (KeyCode::Digit6, EditorTool::Seesaw),
+        (KeyCode::Digit7, EditorTool::Spinner),
+        (KeyCode::Digit8, EditorTool::Bridge),
+        (KeyCode::Digit9, EditorTool::Bolt),
+    ] {
+        if keys.just_pressed(key) {
+            *tool = key_tool;
+        }
+    }
+
+    if keys.pressed(KeyCode::ControlLeft) && keys.just_pressed(KeyCode::KeyS) {
+        status.0 = match save_level(&level.0) {
+            Ok(()) => format!("Saved to {}", editor_level_path().display()),
+            Err(e) => format!("Failed to save: {}", e),
+        };
+    }
+    if keys.just_pressed(KeyCode::Tab) {
+        *mode = GameMode::Playtest;
+        next_state.set(GameState::InGame);
+    }
+    if keys.just_pressed(KeyCode::Escape) {
+        next_state.set(GameState::MainMenu);
+    }
+
+    let Some(selected) = selection.0 else {
+        return;
+    };
+    if keys.any_just_pressed([KeyCode::Delete, KeyCode::Backspace]) {
+        selected.remove(&mut level.0);
+        selection.0 = None;
+        return;
+    }
+    let Selected::Platform(index) = selected else {
+        return;
+    };
+
+    let mut platform = level.0.platforms[index].clone();
+    let step = |decrease: KeyCode, increase: KeyCode| {
+        keys.just_pressed(increase) as i32 as f32 - keys.just_pressed(decrease) as i32 as f32
+    };
+    let width = platform.kind.width_mut();
+    *width = (*width + step(KeyCode::Minus, KeyCode::Equal) * 10.).max(MIN_PLATFORM_WIDTH);
+    match &mut platform.kind {
+        Platform::Moving {
+            path,
+            period,
+            phase,
+            ..
+        } => {
+            path.scale(1. + step(KeyCode::BracketLeft, KeyCode::BracketRight) * 0.1);
+            *period = (*period + step(KeyCode::Comma, KeyCode::Period) * 0.5).max(1.);
+            *phase = (*phase + step(KeyCode::Semicolon, KeyCode::Quote) * 0.125).rem_euclid(1.);
+            if keys.just_pressed(KeyCode::KeyP) {
+                *path = path.next();
+            }
+        }
+        Platform::Spinner { speed, .. } => {
+            *speed += step(KeyCode::Comma, KeyCode::Period) * 0.25;
+        }
+        Platform::Bridge { planks, .. } => {
+            *planks = planks
+                .saturating_add_signed(step(KeyCode::BracketLeft, KeyCode::BracketRight) as i32)
+                .max(1);
+        }
+        Platform::Crumbling { delay, respawn, .. } => {
+            *delay = (*delay + step(KeyCode::Comma, KeyCode::Period) * 0.1).max(0.1);
+            if keys.just_pressed(KeyCode::KeyR) {
+                *respawn = match respawn {
+                    Some(_) => None,
+                    None => Some(3.),
+                };
+            }
+        }
+        _ => {}
+    }
+    if keys.just_pressed(KeyCode::KeyH) {
+        let width = platform.kind.width();
+        platform.kind = if let Platform::Hanging { .. } = platform.kind {
+            Platform::Static { width }
+        } else {
+            Platform::Hanging { width }
+        };
+    }
+
+    if keys.just_pressed(KeyCode::KeyM) {
+        platform.surface = platform.surface.next();
+    }
+
+    if platform.kind != level.0.platforms[index].kind
+        || platform.surface != level.0.platforms[index].surface
+    {
+        level.0.platforms[index] = platform;
+    }
+}
+
+fn save_level(level: &Level) -> Result<(), String> {
+    let serialized =
+        ron::ser::to_string_pretty(level, PrettyConfig::default()).map_err(|e| e.to_string())?;
+    std::fs::write(editor_level_path(), serialized).map_err(|e| e.to_string())
+}
+
+fn editor_mouse(
+    mouse_button: Res<ButtonInput<MouseButton>>,
+    query_window: Query<&Window, With<PrimaryWindow>>,
+    query_camera: Query<(&Camera, &GlobalTransform)>,
+    tool: Res<EditorTool>,
+    mut level: ResMut<EditorLevel>,
+    mut selection: ResMut<EditorSelection>,
+    mut drag: Local<Option<EditorDrag>>,
+) {
+    let Some(cursor) = cursor_in_level(&query_window, &query_camera) else {
+        return;
+    };
+
+    if mouse_button.just_pressed(MouseButton::Left) {
+        let on_selected_edge = match selection.0 {
+            Some(Selected::Platform(index)) => on_edge(&level.0.platforms[index], cursor),
+            _ => false,
+        };
+        if on_selected_edge {
+            *drag = Some(EditorDrag::Resize);
+        } else {
+            let selected =
+                item_at(&level.0, cursor).unwrap_or_else(|| tool.place(&mut level.0, snap(cursor)));
+            selection.0 = Some(selected);
+            *drag = Some(EditorDrag::Move(selected.position(&level.0) - cursor));
+        }
+    } else if mouse_button.pressed(MouseButton::Left) {
+        match (selection.0, *drag) {
+            (Some(selected), Some(EditorDrag::Move(offset))) => {
+                let position = snap(cursor + offset);
+                if position != selected.position(&level.0) {
+                    selected.set_position(&mut level.0, position);
+                }
+            }
+            (Some(Selected::Platform(index)), Some(EditorDrag::Resize)) => {
+                let platform = &level.0.platforms[index];
+                let width = (snap(cursor - platform.position).x.abs() * 2.).max(MIN_PLATFORM_WIDTH);
+                if width != platform.kind.width() {
+                    *level.0.platforms[index].kind.width_mut() = width;
+                }
+            }
+            _ => {}
+        }
+    } else {
+        *drag = None;
+    }
+
+    if mouse_button.just_pressed(MouseButton::Right) {
+        if let Some(selected) = item_at(&level.0, cursor) {
+            selected.remove(&mut level.0);
+            selection.0 = None;
+        }
+    }
+}
+
+fn scroll_editor(
+    time: Res<Time>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut wheel_events: EventReader<MouseWheel>,
+    mut query_camera: Query<&mut Transform, With<Camera>>,
+) {
+    let mut scroll: f32 = wheel_events.read().map(|event| event.y * 20.).sum();
+    if !keys.pressed(KeyCode::ControlLeft) {
+        if keys.pressed(KeyCode::KeyW) {
+            scroll += SCROLL_SPEED * time.delta_seconds();
+        }
+        if keys.pressed(KeyCode::KeyS) {
+            scroll -= SCROLL_SPEED * time.delta_seconds();
+        }
+    }
+
+    for mut transform in query_camera.iter_mut() {
+        transform.translation.y = (transform.translation.y + scroll).max(0.);
+    }
+}
+
+fn draw_selection(level: Res<EditorLevel>, selection: Res<EditorSelection>, mut gizmos: Gizmos) {
+    let color = Color::srgb(1., 1., 0.);
+    match selection.0 {
+        Some(Selected::Platform(index)) => {
+            let platform = &level.0.platforms[index];
+            let position = EDITOR_ORIGIN + platform.position;
+            gizmos.rect_2d(
+                position,
+                0.,
+                Vec2::new(platform.kind.width() + 4., 24.),
+                color,
+            );
+            // Handles to drag the platform wider or narrower by.
+            for side in [-1., 1.] {
+                gizmos.rect_2d(
+                    position + Vec2::new(side * platform.kind.width() / 2., 0.),
+                    0.,
+                    Vec2::splat(EDGE_HANDLE_SIZE * 2.),
+                    color,
+                );
+            }
+            if let Platform::Moving { path, .. } = &platform.kind {
+                gizmos.linestrip_2d(
+                    (0..=32).map(|i| position + path.offset(i as f32 / 32.)),
+                    color,
+                );
+            }
+        }
+        Some(Selected::Bolt(index)) => {
+            gizmos.circle_2d(EDITOR_ORIGIN + level.0.bolts[index], 8., color);
+        }
+        None => {}
+    }
+}
+
+fn update_editor_text(
+    level: Res<EditorLevel>,
+    tool: Res<EditorTool>,
+    selection: Res<EditorSelection>,
+    status: Res<EditorStatus>,
+    mut query_text: Query<&mut Text>,
+    query_editor_text: Query<&Children, With<EditorText>>,
+) {
+    if !(level.is_changed() || tool.is_changed() || selection.is_changed() || status.is_changed()) {
+        return;
+    }
+
+    let selected = match selection.0 {
+        Some(Selected::Platform(index)) => {
+            let platform = &level.0.platforms[index];
+            let kind = match platform.kind {
+                Platform::Moving {
+                    ref path,
+                    period,
+                    phase,
+                    width,
+                } => format!(
+                    "Moving platform, width {:.0}, {} path, period {:.1}, phase {:.2}",
+                    width,
+                    path.name(),
+                    period,
+                    phase
+                ),
+                Platform::Static { width } => format!("Static platform, width {:.0}", width),
+                Platform::Hanging { width } => format!("Hanging platform, width {:.0}", width),
+                Platform::OneWay { width } => format!("One-way platform, width {:.0}", width),
+                Platform::Seesaw { width } => format!("Seesaw, width {:.0}", width),
+                Platform::Spinner { width, speed } => {
+                    format!("Spinner, width {:.0}, speed {:.2}", width, speed)
+                }
+                Platform::Bridge { width, planks } => {
+                    format!("Bridge, width {:.0}, {} planks", width, planks)
+                }
+                Platform::Crumbling {
+                    width,
+                    delay,
+                    respawn,
+                } => format!(
+                    "Crumbling platform, width {:.0}, delay {:.1}, {}",
+                    width,
+                    delay,
+                    respawn.map_or("no respawn".to_owned(), |respawn| format!(
+                        "respawns after {:.0}",
+                        respawn
+                    ))
+                ),
+            };
+            format!("{}, surface {}", kind, platform.surface.name())
+        }
+        Some(Selected::Bolt(_)) => "Bolt".to_owned(),
+        None => "Nothing selected".to_owned(),
+    };
+    let value = format!(
+        "Tool {} (1-9 to change)\n\
+         Click to place or select, drag to move, drag a platform's ends to resize\n\
+         Right click or Delete to remove\n\
+         -/= width  [/] path size or planks  ,/. period, speed or delay  ;/' phase  P path\n\
+         H hanging  R respawn  M surface\n\
+         W/S or wheel to scroll  Tab playtest  Ctrl+S save  Esc menu\n\n\
+         {}\n{}",
+        tool.name(),
+        selected,
+        status.0
+    );
+
+    for children in query_editor_text.iter() {
+        let mut iter = query_text.iter_many_mut(children);
+        while let Some(mut text) = iter.fetch_next() {
+            text.sections[0].value = value.clone();
+        }
+    }
+}
+
+fn create_playtest_level(
+    mut commands: Commands,
+    images: Res<ImageAssets>,
+    asset_server: Res<AssetServer>,
+    level: Res<EditorLevel>,
+) {
+    level
+        .0
+        .spawn(&mut commands, &images, &asset_server, EDITOR_ORIGIN);
+}
+
+fn stop_playtest(keys: Res<ButtonInput<KeyCode>>, mut next_state: ResMut<NextState<GameState>>) {
+    if keys.just_pressed(KeyCode::Tab) {
+        next_state.set(GameState::Editor);
+    }
+}
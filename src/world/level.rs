@@ -0,0 +1,137 @@
+use crate::world::platform::{spawn_bolt, Platform};
+use crate::world::r#box::{BoxBundle, BOX_SIZE};
+use crate::world::surface::Surface;
+use crate::world::WorldEntity;
+use crate::ImageAssets;
+use avian2d::prelude::{AngularVelocity, LinearVelocity};
+use bevy::app::App;
+use bevy::asset::io::Reader;
+use bevy::asset::{Asset, AssetApp, AssetLoader, AssetServer, AsyncReadExt, LoadContext};
+use bevy::math::Vec2;
+use bevy::prelude::{default, Commands, Plugin, SpriteBundle, Transform};
+use bevy::reflect::TypePath;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Level>().init_asset_loader::<LevelLoader>();
+    }
+}
+
+/// A handcrafted piece of world, stored as `.level.ron`. Positions are relative to where the
+/// level is placed, with its lowest platform around y = 0.
+#[derive(Asset, TypePath, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Level {
+    pub platforms: Vec<LevelPlatform>,
+    /// Loose bolts, besides the ones hanging platforms come with.
+    pub bolts: Vec<Vec2>,
+    pub boxes: Vec<Vec2>,
+    pub decorations: Vec<Decoration>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LevelPlatform {
+    pub kind: Platform,
+    pub position: Vec2,
+    #[serde(default)]
+    pub surface: Surface,
+}
+
+/// A sprite drawn behind the platforms, that the blob can't touch.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Decoration {
+    /// Asset path of the image.
+    pub image: String,
+    pub position: Vec2,
+}
+
+impl Level {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Level, ron::error::SpannedError> {
+        ron::de::from_bytes(bytes)
+    }
+
+    /// Spawns the level at `origin`, returning its highest platform and where it was placed.
+    pub fn spawn(
+        &self,
+        commands: &mut Commands,
+        images: &ImageAssets,
+        asset_server: &AssetServer,
+        origin: Vec2,
+    ) -> Option<(Platform, Vec2)> {
+        for platform in self.platforms.iter() {
+            let entity = platform
+                .kind
+                .spawn(commands, images, origin + platform.position);
+            platform.surface.apply(&mut commands.entity(entity), images);
+        }
+        for bolt in self.bolts.iter() {
+            spawn_bolt(commands, images, origin + *bolt);
+        }
+        for (i, position) in self.boxes.iter().enumerate() {
+            commands.spawn(
+                BoxBundle::new(
+                    (origin + *position).extend(0.),
+                    BOX_SIZE,
+                    LinearVelocity::default(),
+                    AngularVelocity::default(),
+                    images.boxes[i % images.boxes.len()].clone(),
+                )
+                .light(),
+            );
+        }
+        for decoration in self.decorations.iter() {
+            commands.spawn((
+                SpriteBundle {
+                    transform: Transform::from_translation(
+                        (origin + decoration.position).extend(-10.),
+                    ),
+                    texture: asset_server.load(decoration.image.clone()),
+                    ..default()
+                },
+                WorldEntity,
+            ));
+        }
+
+        self.platforms
+            .iter()
+            .max_by(|a, b| a.position.y.total_cmp(&b.position.y))
+            .map(|platform| (platform.kind.clone(), origin + platform.position))
+    }
+}
+
+#[derive(Default)]
+struct LevelLoader;
+
+#[non_exhaustive]
+#[derive(Debug, Error)]
+enum LevelLoaderError {
+    #[error("Could not load level: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse level: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+impl AssetLoader for LevelLoader {
+    type Asset = Level;
+    type Settings = ();
+    type Error = LevelLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(Level::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.ron"]
+    }
+}
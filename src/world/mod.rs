@@ -1,33 +1,69 @@
-mod r#box;
-mod platform;
+pub mod r#box;
+pub mod coin;
+mod editor;
+pub mod level;
+pub mod path;
+pub mod platform;
+pub mod surface;
 
-use crate::world::platform::{Platform, PlatformBundle, PlatformsPlugin};
-use crate::{GameState, ImageAssets};
+use crate::events::{Landed, PlatformReached};
+use crate::player::Player;
+use crate::powerup::{roll_power_up, spawn_power_up};
+use crate::world::coin::{roll_coins, spawn_coins, CoinsPlugin};
+use crate::world::editor::EditorPlugin;
+use crate::world::r#box::BoxesPlugin;
+use crate::world::level::{Level, LevelPlugin};
+use crate::world::path::Path;
+use crate::world::platform::{
+    BridgePlank, Platform, PlatformBundle, PlatformsPlugin, PLATFORM_HEIGHT, PLATFORM_WIDTH,
+};
+use crate::world::surface::{Surface, SurfacePlugin};
+use crate::{start_run, GameMode, GameState, ImageAssets, InRun, LevelAssets, PauseState, Run};
+use avian2d::prelude::CollisionStarted;
 use bevy::app::App;
+use bevy::asset::{AssetServer, Assets};
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::{
-    in_state, Camera, Commands, Component, Entity, FixedUpdate, GlobalTransform, IntoSystemConfigs,
-    OnEnter, OnExit, Plugin, Query, Res, ResMut, Resource, Sprite, Time, Timer, TimerMode,
-    Transform, Vec2, Vec3, Window, With,
+    in_state, Camera, Commands, Component, Entity, EventReader, EventWriter, FixedUpdate,
+    GlobalTransform, IntoSystemConfigs, OnEnter, OnExit, Plugin, Query, Res, ResMut, Resource,
+    Sprite, Time, Timer, TimerMode, Transform, Vec2, Vec3, Window, With, Without,
 };
 use bevy::window::PrimaryWindow;
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 
 pub struct WorldPlugin;
 
 impl Plugin for WorldPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(PlatformsPlugin)
+        app.add_plugins((
+            PlatformsPlugin,
+            SurfacePlugin,
+            BoxesPlugin,
+            CoinsPlugin,
+            LevelPlugin,
+            EditorPlugin,
+        ))
             .insert_resource(HighestPlatformInfo::default())
+            .insert_resource(WorldRng(StdRng::seed_from_u64(0)))
+            .insert_resource(LandingInfo::default())
             .insert_resource(DespawnTimer {
                 timer: Timer::from_seconds(1., TimerMode::Repeating),
             })
-            .add_systems(OnEnter(GameState::InGame), create_initial_world_entities)
+            .add_systems(
+                OnEnter(GameState::InGame),
+                create_initial_world_entities.after(start_run),
+            )
             .add_systems(
                 FixedUpdate,
-                (add_platforms, remove_scrolled_out_world_entities)
-                    .run_if(in_state(GameState::InGame)),
+                (
+                    add_platforms.run_if(is_procedural),
+                    detect_landings,
+                    remove_scrolled_out_world_entities,
+                )
+                    .run_if(in_state(PauseState::Running)),
             )
-            .add_systems(OnExit(GameState::GameOver), remove_all_world_entities);
+            .add_systems(OnExit(InRun), remove_all_world_entities);
     }
 }
 
@@ -36,34 +72,78 @@ struct DespawnTimer {
     timer: Timer,
 }
 
+/// Random source for world generation, seeded per run so a layout can be replayed.
+#[derive(Resource)]
+struct WorldRng(StdRng);
+
+static CHUNK_INTERVAL: u32 = 20;
+static MIN_PLATFORM_WIDTH: f32 = 46.;
+/// Height from which on generated platforms are at their narrowest.
+static NARROWEST_HEIGHT: f32 = 5000.;
+static CRUMBLE_DELAY: f32 = 0.8;
+
 #[derive(Resource, Default)]
 
 struct HighestPlatformInfo {
     pos: Vec2,
     platform: Platform,
+    /// Random platforms added since the last handcrafted chunk.
+    since_chunk: u32,
 }
 
+/// Where the blob has been standing, to tell new landings apart from bouncing in place.
+#[derive(Resource, Default)]
+struct LandingInfo {
+    /// The platform landed on last, or the bridge for any of its planks.
+    last_platform: Option<Entity>,
+    highest: f32,
+}
+
+#[derive(Component)]
+pub struct WorldEntity;
+
+/// The floor the blob starts on, which doesn't count as a platform reached.
 #[derive(Component)]
-struct WorldEntity;
+struct Ground;
 
 fn create_initial_world_entities(
     mut commands: Commands,
     images: Res<ImageAssets>,
     mut highest_platform: ResMut<HighestPlatformInfo>,
     mut platform_despawn_timer: ResMut<DespawnTimer>,
+    mut world_rng: ResMut<WorldRng>,
+    mut landing_info: ResMut<LandingInfo>,
+    run: Res<Run>,
 ) {
     platform_despawn_timer.timer.reset();
+    world_rng.0 = StdRng::seed_from_u64(run.seed);
+    *landing_info = LandingInfo {
+        last_platform: None,
+        highest: f32::MIN,
+    };
 
-    commands.spawn(PlatformBundle::new(
-        images.platforms[0].clone(),
-        10000.,
-        1000.,
-        Vec3::new(0., -680., 10.),
-        Platform::Static,
-    ));
+    spawn_ground(&mut commands, &images);
 
     highest_platform.pos = Vec2::new(0., -180.);
-    highest_platform.platform = Platform::Static;
+    highest_platform.platform = Platform::default();
+    highest_platform.since_chunk = 0;
+}
+
+fn spawn_ground(commands: &mut Commands, images: &ImageAssets) {
+    commands.spawn((
+        PlatformBundle::new(
+            images.platforms[0].clone(),
+            10000.,
+            1000.,
+            Vec3::new(0., -680., 10.),
+            Platform::Static { width: 10000. },
+        ),
+        Ground,
+    ));
+}
+
+fn is_procedural(mode: Res<GameMode>) -> bool {
+    mode.is_procedural()
 }
 
 fn remove_all_world_entities(
@@ -75,11 +155,21 @@ fn remove_all_world_entities(
     }
 }
 
+/// The level chunks mixed in between generated platforms.
+#[derive(SystemParam)]
+struct Chunks<'w> {
+    asset_server: Res<'w, AssetServer>,
+    level_assets: Res<'w, LevelAssets>,
+    levels: Res<'w, Assets<Level>>,
+}
+
 fn add_platforms(
     mut commands: Commands,
     query_camera: Query<(&Camera, &GlobalTransform)>,
     images: Res<ImageAssets>,
+    chunks: Chunks,
     mut highest_platform: ResMut<HighestPlatformInfo>,
+    mut world_rng: ResMut<WorldRng>,
 ) {
     let (camera, camera_transform) = query_camera.single();
     let window_top = camera
@@ -88,7 +178,21 @@ fn add_platforms(
         .y;
 
     if window_top > highest_platform.pos.y {
-        let mut rng = rand::thread_rng();
+        let rng = &mut world_rng.0;
+
+        let chunk_handles = &chunks.level_assets.chunks;
+        if highest_platform.since_chunk >= CHUNK_INTERVAL && !chunk_handles.is_empty() {
+            let chunk = &chunk_handles[rng.gen_range(0..chunk_handles.len())];
+            let origin = Vec2::new(0., highest_platform.pos.y + rng.gen_range(60.0..75.0));
+            if let Some((platform, pos)) = chunks.levels.get(chunk).and_then(|level| {
+                level.spawn(&mut commands, &images, &chunks.asset_server, origin)
+            }) {
+                highest_platform.pos = pos;
+                highest_platform.platform = platform;
+                highest_platform.since_chunk = 0;
+                return;
+            }
+        }
 
         let mut pos = highest_platform.pos;
         pos.y += rng.gen_range(60.0..75.0);
@@ -100,23 +204,111 @@ fn add_platforms(
         }
         pos.x = new_x;
 
-        let platform = if highest_platform.platform == Platform::Static {
+        let width = PLATFORM_WIDTH
+            - (PLATFORM_WIDTH - MIN_PLATFORM_WIDTH) * (pos.y / NARROWEST_HEIGHT).clamp(0., 1.);
+        let platform = if let Platform::Static { .. } = highest_platform.platform {
             match rng.gen() {
-                0.0..0.4 => Platform::Static,
-                0.4..0.7 => Platform::Hanging,
-                _ => Platform::Moving {
-                    velocity: rng.gen_range(0.5..1.0),
-                    range: rng.gen_range(20. ..40.),
+                0.0..0.2 => Platform::Static { width },
+                0.2..0.25 => Platform::Bridge {
+                    width: width * 1.6,
+                    planks: 4,
+                },
+                0.25..0.35 => Platform::OneWay { width },
+                0.35..0.5 => Platform::Hanging { width },
+                0.5..0.6 => Platform::Seesaw { width },
+                0.6..0.65 => Platform::Spinner {
+                    width,
+                    speed: rng.gen_range(0.5..1.0) * if rng.gen() { 1. } else { -1. },
+                },
+                0.65..0.85 => Platform::Moving {
+                    path: match rng.gen() {
+                        0.0..0.6 => Path::Horizontal {
+                            extent: rng.gen_range(30. ..70.),
+                        },
+                        0.6..0.8 => Path::Vertical {
+                            extent: rng.gen_range(10. ..20.),
+                        },
+                        _ => Path::Circle {
+                            radius: rng.gen_range(15. ..25.),
+                        },
+                    },
+                    period: rng.gen_range(6. ..12.),
+                    phase: rng.gen(),
+                    width,
+                },
+                _ => Platform::Crumbling {
+                    width,
+                    delay: CRUMBLE_DELAY,
+                    respawn: None,
                 },
             }
         } else {
-            Platform::Static
+            Platform::Static { width }
         };
 
-        platform.spawn(&mut commands, &images, pos);
+        let entity = platform.spawn(&mut commands, &images, pos);
+        if let Platform::Static { .. } = platform {
+            let surface = match rng.gen() {
+                0.0..0.1 => Surface::Bouncy,
+                0.1..0.2 => Surface::Icy,
+                0.2..0.25 => Surface::Sticky,
+                _ => Surface::Plain,
+            };
+            surface.apply(&mut commands.entity(entity), &images);
+        }
+
+        let coins = roll_coins(rng);
+        if coins > 0 {
+            spawn_coins(&mut commands, &images, highest_platform.pos, pos, coins);
+        }
+        if let Some(power_up) = roll_power_up(rng) {
+            spawn_power_up(&mut commands, &images, pos + Vec2::new(0., 30.), power_up);
+        }
 
         highest_platform.pos = pos;
         highest_platform.platform = platform;
+        highest_platform.since_chunk += 1;
+    }
+}
+
+type LandingPlatform = (With<Platform>, Without<Ground>);
+
+fn detect_landings(
+    mut collision_events: EventReader<CollisionStarted>,
+    query_player: Query<(), With<Player>>,
+    query_platform: Query<(&Transform, Option<&BridgePlank>), LandingPlatform>,
+    mut landing_info: ResMut<LandingInfo>,
+    mut landed_event: EventWriter<Landed>,
+    mut platform_reached_event: EventWriter<PlatformReached>,
+) {
+    for CollisionStarted(entity1, entity2) in collision_events.read() {
+        let platform = if query_player.contains(*entity1) {
+            *entity2
+        } else if query_player.contains(*entity2) {
+            *entity1
+        } else {
+            continue;
+        };
+        let Ok((transform, plank)) = query_platform.get(platform) else {
+            continue;
+        };
+        // Rolling from one plank of a bridge onto the next is not a new landing.
+        let landed_on = plank.map_or(platform, |plank| plank.bridge);
+        if landing_info.last_platform == Some(landed_on) {
+            continue;
+        }
+
+        landing_info.last_platform = Some(landed_on);
+        landed_event.send(Landed { platform });
+
+        let top = transform.translation.y + PLATFORM_HEIGHT / 2.;
+        if top > landing_info.highest {
+            landing_info.highest = top;
+            platform_reached_event.send(PlatformReached {
+                platform,
+                height: top,
+            });
+        }
     }
 }
 
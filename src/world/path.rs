@@ -0,0 +1,137 @@
+use bevy::math::Vec2;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::TAU;
+
+/// The track a moving platform follows, relative to where it was placed. One loop of the path
+/// goes from `t = 0` to `t = 1` and ends where it started.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Path {
+    /// Side to side, up to `extent` away.
+    Horizontal { extent: f32 },
+    /// Up and down, up to `extent` away.
+    Vertical { extent: f32 },
+    /// Around a circle, centered on the placed position.
+    Circle { radius: f32 },
+    /// Straight lines through each point in turn and back to the start.
+    Waypoints(Vec<Vec2>),
+    /// Along a cubic Bézier curve to `end` and back.
+    Bezier {
+        control1: Vec2,
+        control2: Vec2,
+        end: Vec2,
+    },
+}
+
+impl Path {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Path::Horizontal { .. } => "horizontal",
+            Path::Vertical { .. } => "vertical",
+            Path::Circle { .. } => "circle",
+            Path::Waypoints(_) => "waypoints",
+            Path::Bezier { .. } => "curve",
+        }
+    }
+
+    /// The next kind of path, with a default shape, for cycling through them in the editor.
+    pub fn next(&self) -> Self {
+        match self {
+            Path::Horizontal { .. } => Path::Vertical { extent: 20. },
+            Path::Vertical { .. } => Path::Circle { radius: 20. },
+            Path::Circle { .. } => Path::Waypoints(vec![
+                Vec2::new(60., 0.),
+                Vec2::new(60., 40.),
+                Vec2::new(0., 40.),
+            ]),
+            Path::Waypoints(_) => Path::Bezier {
+                control1: Vec2::new(0., 60.),
+                control2: Vec2::new(80., 60.),
+                end: Vec2::new(80., 0.),
+            },
+            Path::Bezier { .. } => Path::Horizontal { extent: 40. },
+        }
+    }
+
+    pub fn scale(&mut self, factor: f32) {
+        match self {
+            Path::Horizontal { extent } | Path::Vertical { extent } => *extent *= factor,
+            Path::Circle { radius } => *radius *= factor,
+            Path::Waypoints(points) => points.iter_mut().for_each(|point| *point *= factor),
+            Path::Bezier {
+                control1,
+                control2,
+                end,
+            } => {
+                *control1 *= factor;
+                *control2 *= factor;
+                *end *= factor;
+            }
+        }
+    }
+
+    /// Offset from the placed position at `t` into the loop.
+    pub fn offset(&self, t: f32) -> Vec2 {
+        let t = t.rem_euclid(1.);
+        match self {
+            Path::Horizontal { extent } => Vec2::new((t * TAU).sin() * extent, 0.),
+            Path::Vertical { extent } => Vec2::new(0., (t * TAU).sin() * extent),
+            Path::Circle { radius } => Vec2::from_angle(t * TAU) * *radius,
+            Path::Waypoints(_) => Track::new(self).offset(t),
+            Path::Bezier {
+                control1,
+                control2,
+                end,
+            } => {
+                // There and back, easing into the turns.
+                let s = 1. - (1. - 2. * t).abs();
+                let s = s * s * (3. - 2. * s);
+                let r = 1. - s;
+                3. * r * r * s * *control1 + 3. * r * s * s * *control2 + s * s * s * *end
+            }
+        }
+    }
+}
+
+/// A path made ready to be followed every tick, with the corners and length of a waypoint loop
+/// worked out once.
+#[derive(Clone)]
+pub struct Track {
+    path: Path,
+    corners: Vec<Vec2>,
+    length: f32,
+}
+
+impl Track {
+    pub fn new(path: &Path) -> Self {
+        let corners: Vec<Vec2> = match path {
+            Path::Waypoints(points) => std::iter::once(Vec2::ZERO)
+                .chain(points.iter().copied())
+                .chain(std::iter::once(Vec2::ZERO))
+                .collect(),
+            _ => Vec::new(),
+        };
+        let length = corners.windows(2).map(|w| w[0].distance(w[1])).sum();
+        Track {
+            path: path.clone(),
+            corners,
+            length,
+        }
+    }
+
+    /// Offset from the placed position at `t` into the loop, as in [`Path::offset`].
+    pub fn offset(&self, t: f32) -> Vec2 {
+        let Path::Waypoints(_) = self.path else {
+            return self.path.offset(t);
+        };
+
+        let mut left = t.rem_euclid(1.) * self.length;
+        for segment in self.corners.windows(2) {
+            let segment_length = segment[0].distance(segment[1]);
+            if left <= segment_length && segment_length > 0. {
+                return segment[0].lerp(segment[1], left / segment_length);
+            }
+            left -= segment_length;
+        }
+        Vec2::ZERO
+    }
+}
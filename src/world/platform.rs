@@ -1,28 +1,71 @@
+use crate::events::Landed;
+use crate::player::Player;
+use crate::world::path::{Path, Track};
+use crate::world::surface::Surface;
 use crate::world::WorldEntity;
-use crate::{GameState, ImageAssets};
+use crate::{ImageAssets, PauseState};
 use avian2d::collision::Collider;
 use avian2d::dynamics::solver::xpbd::XpbdConstraint;
 use avian2d::position::{Position, Rotation};
-use avian2d::prelude::{DistanceJoint, Joint, LinearVelocity, RigidBody};
+use avian2d::prelude::{
+    AngularVelocity, ColliderDensity, Collisions, DistanceJoint, Gravity, Joint, LinearVelocity,
+    Mass, Physics, PhysicsSchedule, PhysicsStepSet, PostProcessCollisions, RevoluteJoint,
+    RigidBody, Sensor,
+};
 use bevy::app::{App, Update};
 use bevy::color::Color;
-use bevy::math::{Rect, Vec2, Vec3};
+use bevy::math::{Quat, Rect, Vec2, Vec3};
 use bevy::prelude::{
-    default, in_state, Bundle, Commands, Component, FixedUpdate, Gizmos, Handle, Image,
-    ImageScaleMode, IntoSystemConfigs, Plugin, Query, Res, Sprite, SpriteBundle, Time, Transform,
-    With,
+    default, in_state, Added, Bundle, Commands, Component, Entity, EventReader, FixedUpdate,
+    Gizmos, Handle, Image, ImageScaleMode, IntoSystemConfigs, Plugin, Query, Res, ResMut, Resource,
+    Sprite, SpriteBundle, Time, Timer, TimerMode, Transform, With, Without,
 };
+use bevy::sprite::Anchor;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::f32::consts::TAU;
 
 static PLATFORM_TEXTURE_SIZE: f32 = 46.;
+pub static PLATFORM_WIDTH: f32 = 92.;
+pub static PLATFORM_HEIGHT: f32 = 20.;
+static SHAKE_AMPLITUDE: f32 = 2.;
+static PATH_LOOKAHEAD: f32 = 1. / 30.;
+static SEESAW_DENSITY: f32 = 0.2;
+static SEESAW_TILT: f32 = 0.5;
+static BRIDGE_GAP: f32 = 6.;
+static BRIDGE_SLACK: f32 = 1.6;
+/// Force a rope of a hanging platform can take, in multiples of the platform's weight. Hanging
+/// still, each rope carries half.
+static ROPE_STRENGTH: f32 = 3.;
+static ROPE_SEGMENTS: usize = 8;
+static ROPE_THICKNESS: f32 = 3.;
 
 pub struct PlatformsPlugin;
 
 impl Plugin for PlatformsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, draw_ropes).add_systems(
-            FixedUpdate,
-            scroll_platforms.run_if(in_state(GameState::InGame)),
-        );
+        app.init_resource::<OneWayContacts>()
+            .add_systems(
+                Update,
+                (
+                    (spawn_rope_segments, draw_ropes).chain(),
+                    remove_loose_joints,
+                    draw_snaps,
+                    place_on_paths,
+                ),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    scroll_platforms,
+                    start_crumbling,
+                    crumble_platforms,
+                    respawn_platforms,
+                )
+                    .run_if(in_state(PauseState::Running)),
+            )
+            .add_systems(PostProcessCollisions, filter_one_way_collisions)
+            .add_systems(PhysicsSchedule, snap_ropes.after(PhysicsStepSet::Solver));
     }
 }
 
@@ -49,72 +92,233 @@ impl BoltBundle {
     }
 }
 
-#[derive(Component, Clone, Default, PartialEq)]
+#[derive(Component, Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub enum Platform {
-    #[default]
-    Static,
-    Hanging,
+    Static {
+        width: f32,
+    },
+    Hanging {
+        width: f32,
+    },
+    /// Follows `path` once every `period` seconds, starting `phase` of the way into it.
     Moving {
-        velocity: f32,
-        range: f32,
+        path: Path,
+        period: f32,
+        phase: f32,
+        width: f32,
+    },
+    /// Starts shaking when landed on and falls away `delay` seconds later. Comes back after
+    /// `respawn` seconds, if set.
+    Crumbling {
+        width: f32,
+        delay: f32,
+        respawn: Option<f32>,
+    },
+    /// Can be jumped through from below and landed on from above.
+    OneWay {
+        width: f32,
+    },
+    /// Balanced on a bolt in its middle, tipping under the blob's weight.
+    Seesaw {
+        width: f32,
+    },
+    /// A bar turning around a bolt in its middle at `speed` radians per second.
+    Spinner {
+        width: f32,
+        speed: f32,
     },
+    /// A row of `planks` hung on ropes between two bolts.
+    Bridge {
+        width: f32,
+        planks: u32,
+    },
+}
+
+impl Default for Platform {
+    fn default() -> Self {
+        Platform::Static {
+            width: PLATFORM_WIDTH,
+        }
+    }
 }
 
 impl Platform {
     fn get_rigid_body(&self) -> RigidBody {
         match self {
-            Platform::Static => RigidBody::Static,
-            Platform::Hanging => RigidBody::Dynamic,
-            Platform::Moving {
-                velocity: _,
-                range: _,
-            } => RigidBody::Kinematic,
+            Platform::Static { .. } => RigidBody::Static,
+            Platform::Hanging { .. } | Platform::Seesaw { .. } | Platform::Bridge { .. } => {
+                RigidBody::Dynamic
+            }
+            Platform::Moving { .. } | Platform::Spinner { .. } => RigidBody::Kinematic,
+            Platform::Crumbling { .. } | Platform::OneWay { .. } => RigidBody::Static,
         }
     }
 
     fn get_image_index(&self) -> usize {
         match self {
-            Platform::Static => 0,
-            Platform::Hanging => 1,
-            Platform::Moving {
-                velocity: _,
-                range: _,
-            } => 2,
+            Platform::Static { .. } => 0,
+            Platform::Hanging { .. } | Platform::Seesaw { .. } | Platform::Bridge { .. } => 1,
+            Platform::Moving { .. } | Platform::Spinner { .. } => 2,
+            Platform::Crumbling { .. } => 3,
+            Platform::OneWay { .. } => 4,
+        }
+    }
+
+    pub fn width(&self) -> f32 {
+        match self {
+            Platform::Static { width }
+            | Platform::Hanging { width }
+            | Platform::Moving { width, .. }
+            | Platform::Crumbling { width, .. }
+            | Platform::OneWay { width }
+            | Platform::Seesaw { width }
+            | Platform::Spinner { width, .. }
+            | Platform::Bridge { width, .. } => *width,
+        }
+    }
+
+    pub fn width_mut(&mut self) -> &mut f32 {
+        match self {
+            Platform::Static { width }
+            | Platform::Hanging { width }
+            | Platform::Moving { width, .. }
+            | Platform::Crumbling { width, .. }
+            | Platform::OneWay { width }
+            | Platform::Seesaw { width }
+            | Platform::Spinner { width, .. }
+            | Platform::Bridge { width, .. } => width,
         }
     }
 
-    pub fn spawn(&self, commands: &mut Commands, images: &ImageAssets, pos: Vec2) {
+    /// Spawns the platform at `pos`, returning its body. For bridges that's the middle plank.
+    pub fn spawn(&self, commands: &mut Commands, images: &ImageAssets, pos: Vec2) -> Entity {
+        if let Platform::Bridge { width, planks } = self {
+            return self.spawn_bridge(commands, images, pos, *width, *planks);
+        }
+
+        let width = self.width();
         let platform = commands
             .spawn((PlatformBundle::new(
                 images.platforms[self.get_image_index()].clone(),
-                92.,
-                20.,
+                width,
+                PLATFORM_HEIGHT,
                 pos.extend(0.),
                 self.clone(),
             ),))
             .id();
 
-        if let Platform::Hanging = self {
-            let bolt = commands
-                .spawn(BoltBundle::new(
-                    images.bolt.clone(),
-                    pos.extend(0.) + Vec3::new(0., 50., -5.),
-                ))
-                .id();
+        match self {
+            Platform::Moving { path, .. } => {
+                commands.entity(platform).insert(PathOrigin {
+                    origin: pos,
+                    track: Track::new(path),
+                });
+            }
+            Platform::Seesaw { .. } => {
+                // Light, so the blob can tip it.
+                commands
+                    .entity(platform)
+                    .insert(ColliderDensity(SEESAW_DENSITY));
+                let bolt = spawn_bolt(commands, images, pos);
+                commands.entity(bolt).insert(Pivot);
+                commands.spawn((
+                    RevoluteJoint::new(bolt, platform).with_angle_limits(-SEESAW_TILT, SEESAW_TILT),
+                    WorldEntity,
+                ));
+            }
+            Platform::Spinner { speed, .. } => {
+                // Avian has no joint motors yet, so instead of being driven on a joint the
+                // spinner is kinematic and keeps turning at the same speed whatever lands on it.
+                commands.entity(platform).insert(AngularVelocity(*speed));
+                let bolt = spawn_bolt(commands, images, pos);
+                commands.entity(bolt).insert(Pivot);
+            }
+            _ => {}
+        }
+        if let Platform::Hanging { .. } = self {
+            let bolt = spawn_bolt(commands, images, pos + Vec2::new(0., 50.));
+            let anchor_x = width / 2. - 6.;
+            // Exactly as long as they are, or narrow platforms drop and yank on them right away.
+            let rope_length = Vec2::new(anchor_x, 50.).length();
             commands.spawn((
                 Rope,
                 DistanceJoint::new(bolt, platform)
-                    .with_local_anchor_2(Vec2::new(40., 0.))
-                    .with_rest_length(64.),
+                    .with_local_anchor_2(Vec2::new(anchor_x, 0.))
+                    .with_rest_length(rope_length),
             ));
             commands.spawn((
                 Rope,
                 DistanceJoint::new(bolt, platform)
-                    .with_local_anchor_2(Vec2::new(-40., 0.))
-                    .with_rest_length(64.),
+                    .with_local_anchor_2(Vec2::new(-anchor_x, 0.))
+                    .with_rest_length(rope_length),
             ));
         };
+
+        platform
     }
+
+    fn spawn_bridge(
+        &self,
+        commands: &mut Commands,
+        images: &ImageAssets,
+        pos: Vec2,
+        width: f32,
+        planks: u32,
+    ) -> Entity {
+        let planks = planks.max(1);
+        let spacing = width / planks as f32;
+        let plank_width = spacing - BRIDGE_GAP;
+        let half = Vec2::new(plank_width / 2., 0.);
+
+        let left = spawn_bolt(commands, images, pos - Vec2::new(width / 2., 0.));
+        let right = spawn_bolt(commands, images, pos + Vec2::new(width / 2., 0.));
+        let mut previous = (left, Vec2::ZERO);
+        let mut middle = left;
+        for i in 0..planks {
+            let x = (i as f32 + 0.5) * spacing - width / 2.;
+            let plank = commands
+                .spawn((
+                    PlatformBundle::new(
+                        images.platforms[self.get_image_index()].clone(),
+                        plank_width,
+                        PLATFORM_HEIGHT,
+                        (pos + Vec2::new(x, 0.)).extend(0.),
+                        self.clone(),
+                    ),
+                    BridgePlank { bridge: left },
+                ))
+                .id();
+            spawn_bridge_rope(commands, previous, (plank, -half));
+            previous = (plank, half);
+            if i == planks / 2 {
+                middle = plank;
+            }
+        }
+        spawn_bridge_rope(commands, previous, (right, Vec2::ZERO));
+
+        middle
+    }
+}
+
+/// Links two bridge parts at the given local anchors, with some slack so the bridge sags.
+fn spawn_bridge_rope(
+    commands: &mut Commands,
+    (entity1, anchor1): (Entity, Vec2),
+    (entity2, anchor2): (Entity, Vec2),
+) {
+    commands.spawn((
+        Rope,
+        DistanceJoint::new(entity1, entity2)
+            .with_local_anchor_1(anchor1)
+            .with_local_anchor_2(anchor2)
+            .with_limits(0., BRIDGE_GAP * BRIDGE_SLACK),
+    ));
+}
+
+pub fn spawn_bolt(commands: &mut Commands, images: &ImageAssets, pos: Vec2) -> Entity {
+    commands
+        .spawn(BoltBundle::new(images.bolt.clone(), pos.extend(-5.)))
+        .id()
 }
 
 #[derive(Bundle)]
@@ -167,26 +371,343 @@ struct Bolt;
 #[derive(Component)]
 struct Rope;
 
-fn scroll_platforms(time: Res<Time>, mut platform_query: Query<(&mut LinearVelocity, &Platform)>) {
-    for (mut linear_velocity, platform) in platform_query.iter_mut() {
-        if let Platform::Moving { velocity, range } = platform {
-            linear_velocity.x = (time.elapsed_seconds() * velocity).sin() * range;
+/// One of the planks of a bridge, which all count as the same platform to land on.
+#[derive(Component)]
+pub struct BridgePlank {
+    /// The bolt the bridge hangs from on the left, shared by all of its planks.
+    pub bridge: Entity,
+}
+
+/// One piece of the sprite strip a rope is drawn with.
+#[derive(Component)]
+struct RopeSegment {
+    rope: Entity,
+    index: usize,
+}
+
+/// Where a rope just snapped, shown as a short burst.
+#[derive(Component)]
+struct Snap {
+    timer: Timer,
+    position: Vec2,
+}
+
+/// The bolt a seesaw or spinner turns around.
+#[derive(Component)]
+struct Pivot;
+
+/// Where a moving platform was placed, that its path is relative to.
+#[derive(Component)]
+struct PathOrigin {
+    origin: Vec2,
+    track: Track,
+}
+
+/// Whether the blob passes through the one-way platforms it touches. Decided when it first
+/// touches one and kept for all its squares until it's clear again, so the lattice doesn't snag
+/// with half of it through.
+#[derive(Resource, Default)]
+struct OneWayContacts(HashMap<Entity, bool>);
+
+/// A crumbling platform that was landed on, until it falls.
+#[derive(Component)]
+struct Shaking {
+    timer: Timer,
+}
+
+/// Puts a crumbled platform back once the timer runs out.
+#[derive(Component)]
+struct Respawn {
+    timer: Timer,
+    platform: Platform,
+    surface: Surface,
+    pos: Vec2,
+}
+
+fn scroll_platforms(
+    time: Res<Time<Physics>>,
+    mut platform_query: Query<(&mut LinearVelocity, &Position, &PathOrigin, &Platform)>,
+) {
+    for (mut linear_velocity, position, path_origin, platform) in platform_query.iter_mut() {
+        if let Platform::Moving { period, phase, .. } = platform {
+            // Head for where the path will be a moment from now rather than moving the platform
+            // directly, so it stays kinematic and carries the blob, and never drifts off.
+            let t = (time.elapsed_seconds() + PATH_LOOKAHEAD) / period + phase;
+            linear_velocity.0 =
+                (path_origin.origin + path_origin.track.offset(t) - position.0) / PATH_LOOKAHEAD;
+        }
+    }
+}
+
+/// Puts newly spawned moving platforms where their path is at the moment, instead of where they
+/// were placed, so they don't race across to it on the first tick.
+fn place_on_paths(
+    time: Res<Time<Physics>>,
+    mut platform_query: Query<(&mut Transform, &PathOrigin, &Platform), Added<PathOrigin>>,
+) {
+    for (mut transform, path_origin, platform) in platform_query.iter_mut() {
+        if let Platform::Moving { period, phase, .. } = platform {
+            let t = time.elapsed_seconds() / period + phase;
+            let position = path_origin.origin + path_origin.track.offset(t);
+            transform.translation.x = position.x;
+            transform.translation.y = position.y;
+        }
+    }
+}
+
+fn filter_one_way_collisions(
+    mut collisions: ResMut<Collisions>,
+    mut one_way_contacts: ResMut<OneWayContacts>,
+    query_player: Query<&Position, With<Player>>,
+    query_platform: Query<(&Platform, &Position)>,
+) {
+    let Some(lowest) = query_player
+        .iter()
+        .map(|position| position.y)
+        .reduce(f32::min)
+    else {
+        one_way_contacts.0.clear();
+        return;
+    };
+
+    let mut touching = HashSet::new();
+    collisions.retain(|contacts| {
+        let platform = if query_player.contains(contacts.entity1) {
+            contacts.entity2
+        } else if query_player.contains(contacts.entity2) {
+            contacts.entity1
+        } else {
+            return true;
+        };
+        let Ok((Platform::OneWay { .. }, position)) = query_platform.get(platform) else {
+            return true;
+        };
+
+        touching.insert(platform);
+        // Only a blob that is completely above the surface lands, anything lower came from
+        // below or the side.
+        let passes = *one_way_contacts
+            .0
+            .entry(platform)
+            .or_insert(lowest < position.y + PLATFORM_HEIGHT / 2.);
+        !passes
+    });
+    one_way_contacts
+        .0
+        .retain(|platform, _| touching.contains(platform));
+}
+
+fn start_crumbling(
+    mut commands: Commands,
+    mut landed_events: EventReader<Landed>,
+    query_platform: Query<&Platform, Without<Shaking>>,
+) {
+    for landed in landed_events.read() {
+        if let Ok(Platform::Crumbling { delay, .. }) = query_platform.get(landed.platform) {
+            commands.entity(landed.platform).insert(Shaking {
+                timer: Timer::from_seconds(*delay, TimerMode::Once),
+            });
+        }
+    }
+}
+
+fn crumble_platforms(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query_shaking: Query<(Entity, &mut Shaking, &mut Sprite, &Platform, &Transform)>,
+    query_surface: Query<&Surface>,
+) {
+    for (entity, mut shaking, mut sprite, platform, transform) in query_shaking.iter_mut() {
+        shaking.timer.tick(time.delta());
+        if !shaking.timer.finished() {
+            // Only the sprite shakes, the collider stays in place under the blob.
+            let offset = (shaking.timer.elapsed_secs() * 60.).sin() * SHAKE_AMPLITUDE;
+            sprite.anchor = Anchor::Custom(Vec2::new(offset / platform.width(), 0.));
+            continue;
+        }
+
+        sprite.anchor = Anchor::Center;
+        // Without its Platform and Surface the falling piece no longer counts for anything.
+        commands
+            .entity(entity)
+            .remove::<(Platform, Surface, Shaking)>()
+            .insert((RigidBody::Dynamic, Sensor));
+        if let Platform::Crumbling {
+            respawn: Some(respawn),
+            ..
+        } = platform
+        {
+            commands.spawn((
+                Respawn {
+                    timer: Timer::from_seconds(*respawn, TimerMode::Once),
+                    platform: platform.clone(),
+                    surface: query_surface.get(entity).copied().unwrap_or_default(),
+                    pos: transform.translation.truncate(),
+                },
+                WorldEntity,
+            ));
+        }
+    }
+}
+
+fn respawn_platforms(
+    mut commands: Commands,
+    images: Res<ImageAssets>,
+    time: Res<Time>,
+    mut query_respawn: Query<(Entity, &mut Respawn)>,
+) {
+    for (entity, mut respawn) in query_respawn.iter_mut() {
+        if respawn.timer.tick(time.delta()).finished() {
+            let platform = respawn.platform.spawn(&mut commands, &images, respawn.pos);
+            respawn
+                .surface
+                .apply(&mut commands.entity(platform), &images);
+            commands.entity(entity).despawn();
         }
     }
 }
 
+/// Snaps both ropes of hanging platforms pulled on too hard, like by a hard landing, dropping
+/// the platform.
+fn snap_ropes(
+    mut commands: Commands,
+    gravity: Res<Gravity>,
+    rope_query: Query<(Entity, &DistanceJoint), With<Rope>>,
+    platform_query: Query<(&Platform, &Mass, &Position, &Rotation)>,
+) {
+    let snapped: HashSet<Entity> = rope_query
+        .iter()
+        .filter(|(_, joint)| {
+            matches!(
+                platform_query.get(joint.entity2),
+                Ok((Platform::Hanging { .. }, mass, _, _))
+                    if joint.force.length() > ROPE_STRENGTH * mass.0 * gravity.0.length()
+            )
+        })
+        .map(|(_, joint)| joint.entity2)
+        .collect();
+
+    for (entity, joint) in rope_query.iter() {
+        if let Some((_, _, position, rotation)) = snapped
+            .get(&joint.entity2)
+            .and_then(|platform| platform_query.get(*platform).ok())
+        {
+            commands.entity(entity).despawn();
+            commands.spawn((
+                Snap {
+                    timer: Timer::from_seconds(0.3, TimerMode::Once),
+                    position: position.0 + rotation * joint.local_anchor_2(),
+                },
+                WorldEntity,
+            ));
+        }
+    }
+}
+
+fn draw_snaps(
+    mut commands: Commands,
+    mut gizmos: Gizmos,
+    time: Res<Time>,
+    mut snap_query: Query<(Entity, &mut Snap)>,
+) {
+    for (entity, mut snap) in snap_query.iter_mut() {
+        if snap.timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let progress = snap.timer.fraction();
+        let color = Color::srgba(1.0, 0.6, 0.4, 1. - progress);
+        for i in 0..8 {
+            let direction = Vec2::from_angle(i as f32 * TAU / 8.);
+            gizmos.line_2d(
+                snap.position + direction * (2. + progress * 10.),
+                snap.position + direction * (5. + progress * 14.),
+                color,
+            );
+        }
+    }
+}
+
+fn spawn_rope_segments(
+    mut commands: Commands,
+    images: Res<ImageAssets>,
+    rope_query: Query<Entity, Added<Rope>>,
+) {
+    for rope in rope_query.iter() {
+        for index in 0..ROPE_SEGMENTS {
+            commands.spawn((
+                SpriteBundle {
+                    texture: images.rope.clone(),
+                    ..default()
+                },
+                ImageScaleMode::Tiled {
+                    tile_x: true,
+                    tile_y: false,
+                    stretch_value: 1.0,
+                },
+                RopeSegment { rope, index },
+            ));
+        }
+    }
+}
+
+/// Lays the segments of each rope along a curve between its anchors, sagging by as much as the
+/// rope is longer than the distance it spans, and marks the bolts platforms turn around.
 fn draw_ropes(
+    mut commands: Commands,
     mut gizmos: Gizmos,
     bodies: Query<(&Position, &Rotation)>,
     rope_query: Query<&DistanceJoint, With<Rope>>,
+    mut segment_query: Query<(Entity, &RopeSegment, &mut Transform, &mut Sprite)>,
+    pivot_query: Query<&Position, With<Pivot>>,
 ) {
-    for distance_joint in rope_query.iter() {
-        if let Ok([(pos1, rot1), (pos2, rot2)]) = bodies.get_many(distance_joint.entities()) {
-            gizmos.line_2d(
-                pos1.0 + rot1 * distance_joint.local_anchor_1(),
-                pos2.0 + rot2 * distance_joint.local_anchor_2(),
-                Color::srgb(1.0, 0.6, 0.4),
-            );
+    // The bolt is hidden behind the platform, so mark where it turns.
+    for pivot in pivot_query.iter() {
+        gizmos.circle_2d(pivot.0, 4., Color::srgb(1.0, 0.6, 0.4));
+        gizmos.circle_2d(pivot.0, 1.5, Color::srgb(1.0, 0.6, 0.4));
+    }
+
+    for (entity, segment, mut transform, mut sprite) in segment_query.iter_mut() {
+        let Some((start, end, length)) = rope_query.get(segment.rope).ok().and_then(|joint| {
+            let [(pos1, rot1), (pos2, rot2)] = bodies.get_many(joint.entities()).ok()?;
+            let length = joint
+                .length_limits
+                .map_or(joint.rest_length, |limits| limits.max);
+            Some((
+                pos1.0 + rot1 * joint.local_anchor_1(),
+                pos2.0 + rot2 * joint.local_anchor_2(),
+                length,
+            ))
+        }) else {
+            // Snapped, or one of its ends is gone.
+            commands.entity(entity).despawn();
+            continue;
+        };
+
+        // A shallow parabola with a sag of s spans d with a length of about d + 8s² / 3d.
+        let distance = start.distance(end);
+        let sag = (3. * distance * (length - distance).max(0.) / 8.).sqrt();
+        let control = (start + end) / 2. - Vec2::new(0., 2. * sag);
+        let point = |t: f32| start.lerp(control, t).lerp(control.lerp(end, t), t);
+
+        let from = point(segment.index as f32 / ROPE_SEGMENTS as f32);
+        let to = point((segment.index + 1) as f32 / ROPE_SEGMENTS as f32);
+        *transform = Transform::from_translation(((from + to) / 2.).extend(-6.))
+            .with_rotation(Quat::from_rotation_z((to - from).to_angle()));
+        // A little longer, so there are no gaps at the bends.
+        sprite.custom_size = Some(Vec2::new(from.distance(to) + 1., ROPE_THICKNESS));
+    }
+}
+
+/// Removes joints left behind by bodies that scrolled out of view, which are culled on their own.
+fn remove_loose_joints(
+    mut commands: Commands,
+    bodies: Query<(), With<RigidBody>>,
+    joint_query: Query<(Entity, &RevoluteJoint), With<WorldEntity>>,
+) {
+    for (entity, joint) in joint_query.iter() {
+        if !bodies.contains(joint.entity1) || !bodies.contains(joint.entity2) {
+            commands.entity(entity).despawn();
         }
     }
 }
@@ -0,0 +1,170 @@
+use crate::player::Player;
+use crate::powerup::{ActivePowerUps, PowerUp};
+use crate::world::platform::Platform;
+use crate::world::WorldEntity;
+use crate::{ImageAssets, PauseState};
+use avian2d::position::{Position, Rotation};
+use avian2d::prelude::{
+    CoefficientCombine, CollisionStarted, Friction, Joint, LinearVelocity, Restitution,
+    RevoluteJoint, Sensor,
+};
+use bevy::app::App;
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::{
+    in_state, Commands, Component, Entity, EventReader, FixedUpdate, IntoSystemConfigs, Plugin,
+    Query, Res, Time, Timer, TimerMode, With, Without,
+};
+use serde::{Deserialize, Serialize};
+
+static BOUNCE_VELOCITY: f32 = 550.;
+static GLUE_TIME: f32 = 1.2;
+
+pub struct SurfacePlugin;
+
+impl Plugin for SurfacePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (surface_contacts, release_glue).run_if(in_state(PauseState::Running)),
+        );
+    }
+}
+
+/// What the top of a platform is made of.
+#[derive(Component, Clone, Copy, Default, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Surface {
+    #[default]
+    Plain,
+    /// A trampoline that throws the blob back up.
+    Bouncy,
+    /// Next to no grip.
+    Icy,
+    /// Holds on to the blob for a moment.
+    Sticky,
+}
+
+impl Surface {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Surface::Plain => "Plain",
+            Surface::Bouncy => "Bouncy",
+            Surface::Icy => "Icy",
+            Surface::Sticky => "Sticky",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            Surface::Plain => Surface::Bouncy,
+            Surface::Bouncy => Surface::Icy,
+            Surface::Icy => Surface::Sticky,
+            Surface::Sticky => Surface::Plain,
+        }
+    }
+
+    /// Gives a spawned platform this surface, with its texture and physics material.
+    pub fn apply(&self, platform: &mut EntityCommands, images: &ImageAssets) {
+        platform.insert(*self);
+        match self {
+            Surface::Plain => {}
+            Surface::Bouncy => {
+                platform.insert((
+                    Restitution::new(0.9).with_combine_rule(CoefficientCombine::Max),
+                    images.surfaces[0].clone(),
+                ));
+            }
+            Surface::Icy => {
+                platform.insert((
+                    Friction::new(0.).with_combine_rule(CoefficientCombine::Min),
+                    images.surfaces[1].clone(),
+                ));
+            }
+            Surface::Sticky => {
+                platform.insert((
+                    Restitution::new(0.).with_combine_rule(CoefficientCombine::Min),
+                    images.surfaces[2].clone(),
+                ));
+            }
+        }
+    }
+}
+
+/// Pins a square of the blob to a sticky platform until the timer runs out.
+#[derive(Component)]
+pub struct Glue(Timer);
+
+fn surface_contacts(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionStarted>,
+    mut query_player: Query<&mut LinearVelocity, With<Player>>,
+    query_surface: Query<(&Surface, &Platform), Without<Player>>,
+    query_sensor: Query<(), With<Sensor>>,
+    query_position: Query<(&Position, &Rotation)>,
+    power_ups: Res<ActivePowerUps>,
+) {
+    let clinging = power_ups.has(PowerUp::Sticky);
+    let mut bounce = false;
+    for CollisionStarted(entity1, entity2) in collision_events.read() {
+        let (square, platform) = if query_player.contains(*entity1) {
+            (*entity1, *entity2)
+        } else if query_player.contains(*entity2) {
+            (*entity2, *entity1)
+        } else {
+            continue;
+        };
+
+        let (surface, width) = query_surface
+            .get(platform)
+            .map_or((Surface::Plain, 0.), |(surface, kind)| {
+                (*surface, kind.width())
+            });
+        if surface == Surface::Bouncy {
+            // Only landing on top bounces, not hitting the sides or the underside.
+            let Ok([(square_pos, _), (platform_pos, platform_rot)]) =
+                query_position.get_many([square, platform])
+            else {
+                continue;
+            };
+            let offset = platform_rot.inverse() * (square_pos.0 - platform_pos.0);
+            bounce |= offset.y > 0. && offset.x.abs() <= width / 2.;
+            continue;
+        }
+        // With the sticky power-up, the blob holds on to anything solid, not only to sticky tops.
+        let sticky = surface == Surface::Sticky
+            || clinging && !query_player.contains(platform) && !query_sensor.contains(platform);
+        if !sticky {
+            continue;
+        }
+
+        let Ok([(square_pos, _), (platform_pos, platform_rot)]) =
+            query_position.get_many([square, platform])
+        else {
+            continue;
+        };
+        let anchor = platform_rot.inverse() * (square_pos.0 - platform_pos.0);
+        commands.spawn((
+            RevoluteJoint::new(platform, square).with_local_anchor_1(anchor),
+            Glue(Timer::from_seconds(GLUE_TIME, TimerMode::Once)),
+            WorldEntity,
+        ));
+    }
+
+    // The whole blob gets thrown, not just the squares that touched.
+    if bounce {
+        for mut velocity in query_player.iter_mut() {
+            velocity.y = velocity.y.max(BOUNCE_VELOCITY);
+        }
+    }
+}
+
+fn release_glue(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query_glue: Query<(Entity, &mut Glue)>,
+) {
+    for (entity, mut glue) in query_glue.iter_mut() {
+        if glue.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}